@@ -1,58 +1,188 @@
-use std::fs;
-use clap::Parser;
+use std::{collections::HashMap, fs};
+use clap::{Parser, ValueEnum};
+use itertools::Itertools;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    file_path: String
+    file_path: String,
+    /// Column to compare from (0 based)
+    #[arg(long, default_value_t = 0)]
+    left: usize,
+    /// Column to compare against (0 based)
+    #[arg(long, default_value_t = 1)]
+    right: usize,
+    /// Compare every pair of columns instead of just left and right
+    #[arg(long)]
+    all_pairs: bool,
+    /// How each sorted pair contributes to the distance
+    #[arg(long, value_enum, default_value_t = Metric::Absolute)]
+    metric: Metric,
+    /// List every sorted pair with its contribution
+    #[arg(long, value_enum)]
+    report: Option<ReportFormat>,
+    /// Number of largest contributions to call out in the report
+    #[arg(long, default_value_t = 5)]
+    outliers: usize,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    /// Sum of absolute differences
+    Absolute,
+    /// Sum of squared differences
+    Squared,
+    /// Largest absolute difference
+    Max,
+    /// Number of pairs that differ
+    Mismatches,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Table,
+    Csv,
+}
+
+// Distances are i128 so any two i64 values can be compared, only squaring the very largest gaps can overflow
+impl Metric {
+    fn contribution(&self, num1: i64, num2: i64) -> i128 {
+        let difference = (num1 as i128 - num2 as i128).abs();
+        match self {
+            Metric::Absolute | Metric::Max => difference,
+            Metric::Squared => difference.checked_mul(difference).unwrap_or_else(|| {
+                panic!("Squared difference between {num1} and {num2} is too big")
+            }),
+            Metric::Mismatches => (num1 != num2) as i128,
+        }
+    }
+
+    fn combine(&self, total: i128, contribution: i128) -> i128 {
+        match self {
+            Metric::Max => total.max(contribution),
+            _ => total.checked_add(contribution).expect("Distance is too big to add up"),
+        }
+    }
 }
 
 
+// Every line has the same number of whitespace separated columns, we keep each column as its own list
+fn get_columns (file_path: String) -> Vec<Vec<i64>> {
 
-fn get_sorted_lists (file_path: String) -> (Vec<i32>, Vec<i32>) {
-    
     let raw_data = fs::read_to_string(file_path).expect("Failed to read file");
-    
-    let mut list_1 = Vec::<i32>::new();
-    let mut list_2 = Vec::<i32>::new();
-    for line in raw_data.lines() {
-        let mut nums = line.split_whitespace().map(|x| {
-            x.parse::<i32>().expect("Failed to parse number")
-        });
-        list_1.push(nums.next().unwrap());
-        list_2.push(nums.next().unwrap());
-    }
-    
-    list_1.sort();
-    list_2.sort();
-    (list_1, list_2)
-}
-
-fn solve_puzzle_one(list_1: &[i32], list_2: &[i32]) -> i32 {
+
+    let mut columns = Vec::<Vec<i64>>::new();
+    for (line_number, line) in raw_data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let nums: Vec<i64> = line.split_whitespace().map(|x| {
+            x.parse::<i64>().expect("Failed to parse number")
+        }).collect();
+        if columns.is_empty() {
+            columns = vec![Vec::new(); nums.len()];
+        }
+        if nums.len() != columns.len() {
+            panic!("Line {} has {} columns, expected {}", line_number + 1, nums.len(), columns.len());
+        }
+        for (column, num) in columns.iter_mut().zip(nums) {
+            column.push(num);
+        }
+    }
+    columns
+}
+
+fn frequencies(list: &[i64]) -> HashMap<i64, i64> {
+    let mut counts = HashMap::new();
+    for num in list {
+        *counts.entry(*num).or_insert(0) += 1;
+    }
+    counts
+}
+
+// Pair up the lists smallest to smallest
+fn sorted_pairs(list_1: &[i64], list_2: &[i64]) -> Vec<(i64, i64)> {
+    let sorted_1 = list_1.iter().copied().sorted();
+    let sorted_2 = list_2.iter().copied().sorted();
+    sorted_1.zip(sorted_2).collect()
+}
+
+fn distance(list_1: &[i64], list_2: &[i64], metric: Metric) -> i128 {
     let mut diff = 0;
-    for (num1, num2) in list_1.iter().zip(list_2.iter()) {
-        diff += (num1 - num2).abs();
+    for (num1, num2) in sorted_pairs(list_1, list_2) {
+        diff = metric.combine(diff, metric.contribution(num1, num2));
     }
     diff
 }
 
-fn solve_puzzle_two(list_1: &[i32], list_2: &[i32]) -> i32 {
-    let mut sim_score = 0;
-    for num1  in  list_1.iter() {
-        let mut multiplier = 0;
-        for num2 in list_2.iter(){
-            if num1 == num2 {
-                multiplier += 1;
-            }
+fn print_report(list_1: &[i64], list_2: &[i64], metric: Metric, format: ReportFormat, num_outliers: usize) {
+    let rows: Vec<(usize, i64, i64, i128)> = sorted_pairs(list_1, list_2)
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (num1, num2))| (rank, num1, num2, metric.contribution(num1, num2)))
+        .collect();
+    // Stable sort so ties keep their rank order
+    let outliers: Vec<usize> = rows.iter()
+        .sorted_by(|a, b| b.3.cmp(&a.3))
+        .take(num_outliers)
+        .map(|(rank, ..)| *rank)
+        .collect();
+
+    if format == ReportFormat::Csv {
+        // The outlier column is 1 for the largest contribution, 2 for the next and so on, empty for the rest
+        println!("rank,left,right,contribution,outlier");
+        for (rank, num1, num2, contribution) in rows {
+            let outlier = outliers.iter().position(|outlier| *outlier == rank).map_or(String::new(), |position| (position + 1).to_string());
+            println!("{rank},{num1},{num2},{contribution},{outlier}");
         }
-        sim_score += multiplier*num1;
+        return;
+    }
+
+    println!("{:>6} {:>12} {:>12} {:>14}", "rank", "left", "right", "contribution");
+    for (rank, num1, num2, contribution) in rows.iter() {
+        println!("{rank:>6} {num1:>12} {num2:>12} {contribution:>14}");
+    }
+    println!("\nLargest {} outliers ({:?})", outliers.len(), metric);
+    for (rank, num1, num2, contribution) in outliers.iter().map(|rank| rows[*rank]) {
+        println!("{rank:>6} {num1:>12} {num2:>12} {contribution:>14}");
+    }
+}
+
+// Each number in the first list weighted by how often it shows up in the second, one pass over each list
+fn similarity(list_1: &[i64], list_2: &[i64]) -> i64 {
+    let counts = frequencies(list_2);
+    let mut sim_score = 0;
+    for num1 in list_1.iter() {
+        sim_score += num1 * counts.get(num1).unwrap_or(&0);
     }
     sim_score
 }
 
 fn main(){
     let args = Cli::parse();
-    let (list_1, list_2) = get_sorted_lists(args.file_path);
-    println!("Answer 1: {}", solve_puzzle_one(&list_1, &list_2));
-    println!("Answer 2: {}", solve_puzzle_two(&list_1, &list_2));
-}
\ No newline at end of file
+    let columns = get_columns(args.file_path);
+    for column in [args.left, args.right] {
+        if column >= columns.len() {
+            panic!("Column {} does not exist, the file has {} columns", column, columns.len());
+        }
+    }
+
+    if args.all_pairs {
+        for (left, right) in (0..columns.len()).tuple_combinations() {
+            println!(
+                "Columns {left} and {right}: distance {} similarity {}",
+                distance(&columns[left], &columns[right], args.metric),
+                similarity(&columns[left], &columns[right])
+            );
+        }
+        return;
+    }
+
+    if let Some(format) = args.report {
+        print_report(&columns[args.left], &columns[args.right], args.metric, format, args.outliers);
+        return;
+    }
+
+    println!("Answer 1: {}", distance(&columns[args.left], &columns[args.right], args.metric));
+    println!("Answer 2: {}", similarity(&columns[args.left], &columns[args.right]));
+}
@@ -1,4 +1,6 @@
 use std::{collections::{HashMap, LinkedList}, fs, hash::Hash};
+use adventurust::progress::Progress;
+use adventurust::runner::{measure, write_bench_json};
 use clap::Parser;
 use log::{self, debug, info};
 use itertools::enumerate;
@@ -13,7 +15,10 @@ Approach:
 #[command(version, about, long_about = None)]
 struct Cli {
     file_path: String,
-    num_steps: Option<i32>
+    num_steps: Option<i32>,
+    /// Write phase timings and heap usage to this file as JSON
+    #[arg(long)]
+    bench_json: Option<String>,
 }
 
 
@@ -75,7 +80,8 @@ fn print_stones(stones: &LinkedList<Stone>) {
 
 
 
-fn stone_steps(stone: &Stone, num_steps_left: i32, known_steps: & mut HashMap<Stone, HashMap<i32, LinkedList<Stone>>>) -> LinkedList<Stone> {
+// Progress counts each result added to known_steps, that's where the time goes
+fn stone_steps(stone: &Stone, num_steps_left: i32, known_steps: & mut HashMap<Stone, HashMap<i32, LinkedList<Stone>>>, progress: &mut Progress) -> LinkedList<Stone> {
     debug!("stone_steps: {:?} {:?}", stone, num_steps_left);
     
     if ! known_steps.contains_key(&stone) {
@@ -106,10 +112,11 @@ fn stone_steps(stone: &Stone, num_steps_left: i32, known_steps: & mut HashMap<St
             let last_known_step = known_steps.get(&(stone.clone())).unwrap().get(&max_steps).unwrap().clone();
             let mut new_steps = LinkedList::new();
             for last_known_stone in  last_known_step {
-                let mut steps = stone_steps(&last_known_stone, num_steps_left - max_steps, known_steps);
+                let mut steps = stone_steps(&last_known_stone, num_steps_left - max_steps, known_steps, progress);
                 new_steps.append(&mut steps);
             }
             known_steps.get_mut(&stone).unwrap().insert(num_steps_left, new_steps.clone());
+            progress.inc(1);
             return new_steps
         }
     }
@@ -123,11 +130,12 @@ fn stone_steps(stone: &Stone, num_steps_left: i32, known_steps: & mut HashMap<St
     }
     let mut new_stones_2 = LinkedList::new();
     for new_stone in new_stones {
-        let mut temp = stone_steps(&new_stone, num_steps_left-1, known_steps);
+        let mut temp = stone_steps(&new_stone, num_steps_left-1, known_steps, progress);
         new_stones_2.append(& mut temp);   
     }
     
     known_steps.get_mut(&stone).unwrap().insert(num_steps_left, new_stones_2.clone());
+    progress.inc(1);
 
     new_stones_2
 }
@@ -137,17 +145,31 @@ fn main(){
 
     let args = Cli::parse();
     let file_path = args.file_path;
+    let mut phases = Vec::new();
 
-    let mut stones = get_stones(file_path);
+    let (stones, report) = measure("parse", || get_stones(file_path));
+    info!("{report}");
+    phases.push(report);
     let steps = args.num_steps.or_else(|| Some(6)).unwrap();
     print_stones(&stones);
 
-    let mut new_stones = LinkedList::new();
-    let mut known_steps = HashMap::new();
-    for stone in stones {
-        new_stones.append(& mut stone_steps(&stone, steps, &mut known_steps));
-    }
+    let (new_stones, report) = measure("blink", || {
+        let mut new_stones = LinkedList::new();
+        let mut known_steps = HashMap::new();
+        let mut progress = Progress::new("Memoised blinks", None);
+        for stone in stones {
+            new_stones.append(& mut stone_steps(&stone, steps, &mut known_steps, &mut progress));
+        }
+        progress.finish();
+        new_stones
+    });
+    info!("{report}");
+    phases.push(report);
     info!("Answer 1: {:?}", new_stones.len());
 
+    if let Some(bench_json) = args.bench_json {
+        write_bench_json(&bench_json, "december_11", &phases);
+    }
+
 
 }  
\ No newline at end of file
@@ -1,5 +1,6 @@
 use core::num;
 use std::{array, collections::{HashMap, HashSet}, fs, iter::zip};
+use adventurust::inspect::{InputStats, InspectFormat};
 use clap::Parser;
 use log::{self, debug, info};
 use itertools::enumerate;
@@ -198,6 +199,9 @@ impl GardenArea {
 struct Cli {
     file_path: String,
     target_number: Option<i64>,
+    /// Print summary statistics of the parsed input instead of solving
+    #[arg(long, value_enum)]
+    inspect: Option<InspectFormat>,
 }
 
 
@@ -270,7 +274,7 @@ fn build_gardens(file_map: &Vec<Vec<char>>) -> Vec<GardenArea> {
     areas
 }
 
-fn get_garden_areas(file_path: String) -> Vec<GardenArea> {
+fn get_file_map(file_path: String) -> Vec<Vec<char>> {
    let lines = fs::read_to_string(file_path).expect("Failed to read file");
    let mut file_map = Vec::new();
    for line in lines.lines() {
@@ -280,9 +284,21 @@ fn get_garden_areas(file_path: String) -> Vec<GardenArea> {
        }
        file_map.push(line_vec);
    }
+   file_map
+}
 
-   build_gardens(&file_map)
+fn get_garden_areas(file_path: String) -> Vec<GardenArea> {
+   build_gardens(&get_file_map(file_path))
+}
 
+fn inspect_file_map(file_map: &[Vec<char>]) -> InputStats {
+    let mut stats = InputStats::new();
+    stats
+        .count("width", file_map.first().map_or(0, |line| line.len()))
+        .count("height", file_map.len())
+        .count("distinct plants", file_map.iter().flatten().collect::<HashSet<_>>().len())
+        .histogram("plots per plant", file_map.iter().flatten());
+    stats
 }
 
 
@@ -291,6 +307,10 @@ fn main(){
 
     let args = Cli::parse();
     let file_path = args.file_path;
+    if let Some(format) = args.inspect {
+        inspect_file_map(&get_file_map(file_path)).print(format);
+        return;
+    }
     let file_map = get_garden_areas(file_path);
 
     let mut sum = 0;
@@ -1,11 +1,25 @@
-use std::cmp;
 use std::fs;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    file_path: String
+    file_path: String,
+    /// Smallest allowed difference between neighbouring levels
+    #[arg(long, default_value_t = 1)]
+    min_step: i32,
+    /// Largest allowed difference between neighbouring levels
+    #[arg(long, default_value_t = 3)]
+    max_step: i32,
+    #[arg(long, value_enum, default_value_t = Direction::Either)]
+    direction: Direction,
+    /// Number of levels the problem dampener may remove for part two
+    #[arg(long, default_value_t = 1)]
+    removals: usize,
+    /// Print a verdict for every report as JSON lines instead of the answers
+    #[arg(long)]
+    explain: bool,
 }
 
 
@@ -25,60 +39,178 @@ fn get_list_of_levels (file_path: String) -> Vec<Vec<i32>> {
     lists
 }
 
-fn check_if_safe(levels: &[i32]) -> Option<usize> {
-    let len = levels.len();
-    let slice_1 = &levels[..len-1];
-    let slice_2 =  &levels[1..];
-    let ascending = (slice_1[0] - slice_2[0]) > 0;
-    for i in 0..len-1 {
-        let diff = slice_1[i] - slice_2[i];
-        let monotonic_check = (ascending && diff > 0) || (!ascending && diff < 0);
-        let step_check = (diff.abs() > 0) && (diff.abs() <= 3);
-        if !monotonic_check || !step_check {
-            return Some(i);
-        } 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Increasing,
+    Decreasing,
+    /// Strictly monotonic, whichever way the report goes
+    Either,
+    /// Only the step size is checked
+    Any,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rules {
+    min_step: i32,
+    max_step: i32,
+    direction: Direction,
+    max_removals: usize,
+}
+
+fn direction_is_valid(diff: i32, direction: Direction) -> bool {
+    match direction {
+        Direction::Increasing => diff > 0,
+        Direction::Decreasing => diff < 0,
+        Direction::Either | Direction::Any => true,
     }
-    None
 }
 
-fn solve_puzzle_one(lists: &Vec<Vec<i32>>) -> i32 {
-    let mut valid = 0;
-    
-    for levels in lists {
-       let bad_index = check_if_safe(levels);
-       if bad_index.is_none(){
-            valid+=1;
-       }
+impl Rules {
+    fn step_is_valid(&self, from: i32, to: i32, direction: Direction) -> bool {
+        let diff = to - from;
+        direction_is_valid(diff, direction) && diff.abs() >= self.min_step && diff.abs() <= self.max_step
     }
+}
 
-    valid
+/*
+Removing levels leaves a subsequence, and with at most k removals two neighbouring
+kept levels are never more than k+1 apart. So for each level we only need to look back
+k+1 places for the cheapest valid chain ending there:
+
+removals[i] = min(i, removals[j] + (i - j - 1)) for j in i-k-1..i where j -> i is a valid step
+
+and the report is safe when some chain end i has removals[i] + (n - 1 - i) <= k.
+That's O(n*k) per direction and it considers every removal set, not just those near the
+first failure.
+*/
+fn removals_for_direction(levels: &[i32], rules: &Rules, direction: Direction) -> Option<Vec<usize>> {
+    let len = levels.len();
+    if len == 0 {
+        return Some(Vec::new());
+    }
+    let k = rules.max_removals;
+    let mut removals = vec![0; len];
+    let mut previous: Vec<Option<usize>> = vec![None; len];
+    for i in 0..len {
+        removals[i] = i;
+        for j in i.saturating_sub(k + 1)..i {
+            let cost = removals[j] + (i - j - 1);
+            if cost < removals[i] && rules.step_is_valid(levels[j], levels[i], direction) {
+                removals[i] = cost;
+                previous[i] = Some(j);
+            }
+        }
+    }
+
+    let (last, total) = (0..len)
+        .map(|i| (i, removals[i] + (len - 1 - i)))
+        .min_by_key(|(_, total)| *total)
+        .unwrap();
+    if total > k {
+        return None;
+    }
+
+    let mut kept = vec![false; len];
+    let mut current = Some(last);
+    while let Some(i) = current {
+        kept[i] = true;
+        current = previous[i];
+    }
+    Some((0..len).filter(|i| !kept[*i]).collect())
 }
 
+// The smallest set of levels to remove to make the report safe, if it can be done within the rules
+fn safe_removals(levels: &[i32], rules: &Rules) -> Option<Vec<usize>> {
+    let directions = match rules.direction {
+        Direction::Either => vec![Direction::Increasing, Direction::Decreasing],
+        direction => vec![direction],
+    };
+    directions
+        .into_iter()
+        .filter_map(|direction| removals_for_direction(levels, rules, direction))
+        .min_by_key(|removed| removed.len())
+}
 
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Violation {
+    DirectionChange,
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
+}
 
-fn solve_puzzle_two(lists: &Vec<Vec<i32>>) -> i32 {
-    let mut valid = 0;
-    
-    for levels in lists {
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Safe,
+    Dampened,
+    Unsafe,
+}
+
+#[derive(Serialize, Debug)]
+struct Verdict {
+    report: usize,
+    levels: Vec<i32>,
+    status: Status,
+    // Index of the level that first breaks a rule in the undampened report
+    failing_index: Option<usize>,
+    violation: Option<Violation>,
+    removed_indices: Vec<usize>,
+    removed_levels: Vec<i32>,
+}
 
-        let bad_index= check_if_safe(levels);
-        if bad_index.is_none(){
-            
-            valid +=1;
-            continue;
+// The first rule broken walking the report left to right, the direction is set by the first step when either is allowed
+fn first_violation(levels: &[i32], rules: &Rules) -> Option<(usize, Violation)> {
+    let mut direction = rules.direction;
+    for i in 1..levels.len() {
+        let diff = levels[i] - levels[i - 1];
+        if direction == Direction::Either {
+            direction = if diff > 0 { Direction::Increasing } else { Direction::Decreasing };
         }
+        let violation = if diff == 0 && rules.min_step > 0 {
+            Some(Violation::ZeroStep)
+        } else if !direction_is_valid(diff, direction) {
+            Some(Violation::DirectionChange)
+        } else if diff.abs() < rules.min_step {
+            Some(Violation::StepTooSmall)
+        } else if diff.abs() > rules.max_step {
+            Some(Violation::StepTooLarge)
+        } else {
+            None
+        };
+        if let Some(violation) = violation {
+            return Some((i, violation));
+        }
+    }
+    None
+}
+
+fn explain(report: usize, levels: &[i32], rules: &Rules) -> Verdict {
+    let failure = first_violation(levels, rules);
+    let removed = if failure.is_some() { safe_removals(levels, rules) } else { Some(Vec::new()) };
+    let status = match (&failure, &removed) {
+        (None, _) => Status::Safe,
+        (Some(_), Some(_)) => Status::Dampened,
+        (Some(_), None) => Status::Unsafe,
+    };
+    let removed_indices = removed.unwrap_or_default();
+    Verdict {
+        report,
+        levels: levels.to_vec(),
+        status,
+        failing_index: failure.map(|(index, _)| index),
+        violation: failure.map(|(_, violation)| violation),
+        removed_levels: removed_indices.iter().map(|i| levels[*i]).collect(),
+        removed_indices,
+    }
+}
 
-        // Try both the problem child and those around it
-        let lower = if bad_index.unwrap() < 2 { 0 } else { bad_index.unwrap() - 2};
-        let upper = cmp::min(bad_index.unwrap()+2,levels.len());
-        for i in lower..upper{
-            let mut levels_copy = levels.clone();
-            levels_copy.remove(i);
-            let second_try = check_if_safe(&levels_copy);
-            if second_try.is_none(){
-                valid +=1;
-                break;
-            } 
+fn count_safe(lists: &Vec<Vec<i32>>, rules: &Rules) -> i32 {
+    let mut valid = 0;
+    for levels in lists {
+        if safe_removals(levels, rules).is_some() {
+            valid += 1;
         }
     }
     valid
@@ -87,7 +219,22 @@ fn solve_puzzle_two(lists: &Vec<Vec<i32>>) -> i32 {
 fn main(){
     let args = Cli::parse();
     let lists = get_list_of_levels(args.file_path);
+    let rules = Rules {
+        min_step: args.min_step,
+        max_step: args.max_step,
+        direction: args.direction,
+        max_removals: 0,
+    };
+    if args.explain {
+        let dampened_rules = Rules { max_removals: args.removals, ..rules };
+        for (report, levels) in lists.iter().enumerate() {
+            let verdict = explain(report, levels, &dampened_rules);
+            println!("{}", serde_json::to_string(&verdict).expect("Failed to serialize verdict"));
+        }
+        return;
+    }
+
     println!("First Line: {:?}", lists.first().unwrap());
-    println!("Answer 1: {}", solve_puzzle_one(&lists));
-    println!("Answer 2: {}", solve_puzzle_two(&lists));
+    println!("Answer 1: {}", count_safe(&lists, &rules));
+    println!("Answer 2: {}", count_safe(&lists, &Rules { max_removals: args.removals, ..rules }));
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use std::{collections::HashMap, fs, hash::Hash};
+use adventurust::inspect::{InputStats, InspectFormat};
 use clap::Parser;
 use itertools::Itertools;
 use log::{self, debug, error, info, Log};
@@ -126,6 +127,9 @@ struct Cli {
     file_path: String,
     num_1: Option<i32>,
     num_2: Option<i32>,
+    /// Print summary statistics of the parsed input instead of solving
+    #[arg(long, value_enum)]
+    inspect: Option<InspectFormat>,
 }
 
 fn read(file_path: String) -> HashMap<String, LogicGate> {
@@ -165,6 +169,37 @@ fn read(file_path: String) -> HashMap<String, LogicGate> {
 
 }
 
+// Longest chain of gates between an input wire and this gate
+fn gate_depth(name: &str, gates: &HashMap<String, LogicGate>, depths: &mut HashMap<String, usize>) -> usize {
+    if let Some(depth) = depths.get(name) {
+        return *depth;
+    }
+    let gate = &gates[name];
+    let depth = if gate.input_names.is_empty() {
+        0
+    } else {
+        1 + gate.input_names.iter().map(|input| gate_depth(input, gates, depths)).max().unwrap()
+    };
+    depths.insert(name.to_string(), depth);
+    depth
+}
+
+fn inspect_gates(gates: &HashMap<String, LogicGate>) -> InputStats {
+    let mut depths = HashMap::new();
+    let logic_gates: Vec<&LogicGate> = gates.values().filter(|gate| !gate.input_names.is_empty()).collect();
+    let mut stats = InputStats::new();
+    stats
+        .count("input wires", gates.len() - logic_gates.len())
+        .count("gates", logic_gates.len())
+        .count("output wires", gates.keys().filter(|name| name.starts_with('z')).count())
+        .histogram("gates by operation", logic_gates.iter().map(|gate| format!("{:?}", gate.op)));
+    let gate_depths: Vec<usize> = logic_gates.iter().map(|gate| gate_depth(&gate.name, gates, &mut depths)).collect();
+    stats
+        .extrema("depth", gate_depths.iter().map(|depth| *depth as i64))
+        .histogram("gates by depth", gate_depths);
+    stats
+}
+
 fn vec_binary_to_bool_int(vec: &Vec<bool>) -> i64 {
     let mut result = 0;
     for (i, b) in vec.iter().enumerate() {
@@ -191,6 +226,10 @@ fn main(){
     let args = Cli::parse();
     let file_path = args.file_path;
     let mut gates = read(file_path);
+    if let Some(format) = args.inspect {
+        inspect_gates(&gates).print(format);
+        return;
+    }
     if args.num_1.is_some() && args.num_2.is_some() {
         let num_1 = args.num_1.unwrap();
         let num_2 = args.num_2.unwrap();
@@ -1,114 +1,344 @@
-use std::fs;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::str::FromStr;
 use clap::Parser;
-use regex::Regex;
-use std::collections::BTreeMap;
+use log::{self, debug, warn};
 
 
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    file_path: String
+    file_path: String,
+    /// Register an extra instruction as NAME:ARGS:DIGITS:SEMANTICS, e.g. add:2-4:3:add or flip:0:0:toggle.
+    /// Semantics are mul, add, sub, enable, disable or toggle
+    #[arg(long)]
+    instruction: Vec<InstructionSpec>,
+    /// Print every recognised instruction with its byte offset and effect on the accumulator
+    #[arg(long)]
+    trace: bool,
+    /// Bytes read from the file at a time, tiny chunks let the tests split instructions across reads
+    #[arg(long, default_value_t = CHUNK_SIZE as u64, value_parser = clap::value_parser!(u64).range(1..), hide = true)]
+    chunk_size: u64,
 }
 
+/*
+One pass over the corrupted memory: the tokenizer slides along a small buffer of bytes
+read from the file and tries to recognise an instruction at each position, the interpreter
+runs them in order while tracking whether muls are enabled.
 
+Instructions are only a few bytes, so when a partial match hits the end of the
+buffer we read another chunk and try again. Anything already scanned is dropped, so
+memory stays flat however big the file is.
+*/
 
-fn get_raw_string (file_path: String) -> String {
-    
-    let read_to_string = fs::read_to_string(file_path).expect("Failed to read file");
-    let raw_data = read_to_string;
+const CHUNK_SIZE: usize = 64 * 1024;
 
-    let mut string = String::new();
+// Every 18 digit number fits in an i64
+const MAX_DIGITS: usize = 18;
 
-    for line in raw_data.lines() {
-        string = [string, line.to_string()].join("");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Semantics {
+    /// Add the product of the operands to the accumulator
+    Mul,
+    /// Add the sum of the operands to the accumulator
+    Add,
+    /// Add the first operand minus the rest to the accumulator
+    Sub,
+    Enable,
+    Disable,
+    Toggle,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InstructionSpec {
+    name: String,
+    min_args: usize,
+    max_args: usize,
+    max_digits: usize,
+    semantics: Semantics,
+}
+
+// Parsed from NAME:ARGS:DIGITS:SEMANTICS, where ARGS is a count or a range like 2-4
+impl FromStr for InstructionSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        if parts.len() != 4 {
+            return Err(format!("Expected NAME:ARGS:DIGITS:SEMANTICS, got {spec}"));
+        }
+        let (min_args, max_args) = match parts[1].split_once('-') {
+            Some((min, max)) => (min.parse(), max.parse()),
+            None => (parts[1].parse(), parts[1].parse()),
+        };
+        let semantics = match parts[3] {
+            "mul" => Semantics::Mul,
+            "add" => Semantics::Add,
+            "sub" => Semantics::Sub,
+            "enable" => Semantics::Enable,
+            "disable" => Semantics::Disable,
+            "toggle" => Semantics::Toggle,
+            other => return Err(format!("Unknown semantics {other}")),
+        };
+        let spec = InstructionSpec {
+            name: parts[0].to_string(),
+            min_args: min_args.map_err(|_| format!("Bad argument count in {spec}"))?,
+            max_args: max_args.map_err(|_| format!("Bad argument count in {spec}"))?,
+            max_digits: parts[2].parse().map_err(|_| format!("Bad digit count in {spec}"))?,
+            semantics,
+        };
+        if spec.name.is_empty() || spec.min_args > spec.max_args {
+            return Err(format!("Invalid instruction {spec:?}"));
+        }
+        if spec.max_args > 0 && spec.max_digits == 0 {
+            return Err(format!("Instruction {} takes arguments so needs at least one digit", spec.name));
+        }
+        if spec.max_digits > MAX_DIGITS {
+            return Err(format!("Instruction {} can have at most {MAX_DIGITS} digit operands", spec.name));
+        }
+        Ok(spec)
     }
-    
-    string
 }
 
-fn get_matches (string: &str) -> BTreeMap<usize,String> {
-    let re = Regex::new(r"mul[(][0-9]{1,3},[0-9]{1,3}[)]").unwrap();
-    let mut matches = BTreeMap::<usize,String>::new();
-    for mat in re.find_iter(string){
-        matches.insert(
-            mat.start(),
-            mat.as_str().to_string()
-          );
+#[derive(Debug, Clone)]
+struct InstructionSet {
+    specs: Vec<InstructionSpec>,
+}
+
+impl InstructionSet {
+    // The instructions from the puzzle: mul(a,b) with up to three digit operands, do() and don't()
+    fn puzzle() -> Self {
+        let mut set = InstructionSet { specs: Vec::new() };
+        set.register("mul:2:3:mul".parse().unwrap())
+            .register("do:0:0:enable".parse().unwrap())
+            .register("don't:0:0:disable".parse().unwrap());
+        set
+    }
+
+    // Registering a name that already exists replaces it
+    fn register(&mut self, spec: InstructionSpec) -> &mut Self {
+        self.specs.retain(|existing| existing.name != spec.name);
+        self.specs.push(spec);
+        self
     }
-    matches
 }
 
-fn get_dos_and_donts(string: &str) -> Vec<(usize, usize)> {
-    let re_do = Regex::new(r"do[(][)]").unwrap();
-    let re_dont = Regex::new(r"don't[(][)]").unwrap();
-    
-    let mut do_captures = re_do.find_iter(string).map(|x| x.start());
-    let mut dont_captures = re_dont.find_iter(string).map(|x| x.start());
-    
-    let mut dos = Vec::<(usize, usize)>::new();
-    let mut dos_current = Some(0);
-    let mut dont_current = dont_captures.next();
-    let mut store_next = true;
-    while dos_current.is_some() && dont_current.is_some() {
-        if dont_current.unwrap() <  dos_current.unwrap() {
-            store_next = true;
-            dont_current = dont_captures.next();
-        } else {
-            if store_next {
-                dos.push((dos_current.unwrap(), dont_current.unwrap()));
-                store_next = false;
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Instruction {
+    spec: usize,
+    operands: Vec<i64>,
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Parse {
+    Token(Instruction, usize),
+    NoMatch,
+    NeedMore,
+}
+
+struct Matcher<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Matcher<'_> {
+    fn literal(&mut self, literal: &[u8]) -> Result<(), Parse> {
+        for expected in literal {
+            match self.input.get(self.pos) {
+                None => return Err(Parse::NeedMore),
+                Some(byte) if byte != expected => return Err(Parse::NoMatch),
+                Some(_) => self.pos += 1,
             }
-            dos_current = do_captures.next();
         }
+        Ok(())
+    }
+
+    fn peek(&self) -> Result<u8, Parse> {
+        self.input.get(self.pos).copied().ok_or(Parse::NeedMore)
+    }
+
+    // Between one and `max_digits` digits, a longer number then fails on whatever literal comes next
+    fn number(&mut self, max_digits: usize) -> Result<i64, Parse> {
+        let mut value = 0;
+        let mut digits = 0;
+        while digits < max_digits {
+            match self.input.get(self.pos) {
+                None => return Err(Parse::NeedMore),
+                Some(byte) if byte.is_ascii_digit() => {
+                    value = value * 10 + (byte - b'0') as i64;
+                    digits += 1;
+                    self.pos += 1;
+                }
+                Some(_) => break,
+            }
+        }
+        if digits == 0 {
+            return Err(Parse::NoMatch);
+        }
+        Ok(value)
     }
-    
-    dos
 }
 
-fn calc_mult(mult: &str) -> i32 {
-    let re = Regex::new(r"[0-9]{1,3}").unwrap();
-    let mut num = 1;
-    for num_str in re.find_iter(mult){
-        num *= num_str.as_str().parse::<i32>().unwrap();
+fn parse_instruction(input: &[u8], spec: &InstructionSpec) -> Result<(Vec<i64>, usize), Parse> {
+    let mut matcher = Matcher { input, pos: 0 };
+    matcher.literal(spec.name.as_bytes())?;
+    matcher.literal(b"(")?;
+    let mut operands = Vec::new();
+    if spec.max_args > 0 && matcher.peek()? != b')' {
+        operands.push(matcher.number(spec.max_digits)?);
+        while operands.len() < spec.max_args && matcher.peek()? == b',' {
+            matcher.literal(b",")?;
+            operands.push(matcher.number(spec.max_digits)?);
+        }
+    }
+    matcher.literal(b")")?;
+    if operands.len() < spec.min_args {
+        return Err(Parse::NoMatch);
+    }
+    Ok((operands, matcher.pos))
+}
+
+// Longest match wins, but only once every instruction that could still match has enough input to decide
+fn parse_at(input: &[u8], set: &InstructionSet, eof: bool) -> Parse {
+    let mut best: Option<(Instruction, usize)> = None;
+    for (index, spec) in set.specs.iter().enumerate() {
+        match parse_instruction(input, spec) {
+            Ok((operands, len)) => {
+                if best.as_ref().is_none_or(|(_, best_len)| len > *best_len) {
+                    let text = String::from_utf8_lossy(&input[..len]).to_string();
+                    best = Some((Instruction { spec: index, operands, text }, len));
+                }
+            }
+            Err(Parse::NeedMore) if !eof => return Parse::NeedMore,
+            Err(_) => {}
+        }
+    }
+    match best {
+        Some((instruction, len)) => Parse::Token(instruction, len),
+        None => Parse::NoMatch,
     }
-    num
 }
 
-fn add_mults(matches: &Vec<String>) -> i32 {
-    let mut sum = 0;
-    for mult in matches {
-        sum += calc_mult(mult);
+struct Tokenizer<'a, R: Read> {
+    reader: R,
+    set: &'a InstructionSet,
+    chunk: Vec<u8>,
+    buffer: Vec<u8>,
+    pos: usize,
+    // Byte offset in the file of buffer[0]
+    base_offset: usize,
+    eof: bool,
+}
+
+impl<'a, R: Read> Tokenizer<'a, R> {
+    fn new(reader: R, set: &'a InstructionSet, chunk_size: usize) -> Self {
+        Tokenizer { reader, set, chunk: vec![0; chunk_size], buffer: Vec::new(), pos: 0, base_offset: 0, eof: false }
+    }
+
+    fn fill(&mut self) -> bool {
+        if self.pos > self.chunk.len() {
+            self.buffer.drain(..self.pos);
+            self.base_offset += self.pos;
+            self.pos = 0;
+        }
+        let read = self.reader.read(&mut self.chunk).expect("Failed to read file");
+        if read == 0 {
+            self.eof = true;
+            return false;
+        }
+        self.buffer.extend_from_slice(&self.chunk[..read]);
+        true
     }
-    sum
 }
 
-fn filter_mults(matches: &BTreeMap<usize,String>, dos: &[(usize,usize)]) -> BTreeMap<usize,String> {
-    let mut filtered_matches = BTreeMap::<usize,String>::new();
-    let mut do_iter = dos.iter();
-    let mut current_pair = do_iter.next().unwrap();
-    let (mut start, mut end) = *current_pair;
+impl<R: Read> Iterator for Tokenizer<'_, R> {
+    // The byte offset of the instruction and the instruction itself
+    type Item = (usize, Instruction);
 
-    for (key, val) in matches {
-        while *key > end {
-            current_pair = match do_iter.next() {
-                Some(pair) => pair,
-                None => return filtered_matches,
-            };
-            start = current_pair.0;
-            end = current_pair.1;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.pos >= self.buffer.len() && (self.eof || !self.fill()) {
+                return None;
+            }
+            match parse_at(&self.buffer[self.pos..], self.set, self.eof) {
+                Parse::Token(instruction, len) => {
+                    let offset = self.base_offset + self.pos;
+                    self.pos += len;
+                    return Some((offset, instruction));
+                }
+                Parse::NeedMore => {
+                    self.fill();
+                }
+                Parse::NoMatch => self.pos += 1,
+            }
         }
-        if *key >= start && *key <= end {
-            filtered_matches.insert(*key, val.to_string());
+    }
+}
+
+#[derive(Debug, Default)]
+struct Interpreter {
+    disabled: bool,
+    total: i64,
+    accumulator: i64,
+}
+
+impl Interpreter {
+    // Runs the instruction and describes what it did for the trace, one that would overflow is ignored
+    fn execute(&mut self, instruction: &Instruction, set: &InstructionSet) -> String {
+        let spec = &set.specs[instruction.spec];
+        let operands = &instruction.operands;
+        let value = match spec.semantics {
+            Semantics::Mul => operands.iter().try_fold(1_i64, |product, operand| product.checked_mul(*operand)),
+            Semantics::Add => operands.iter().try_fold(0_i64, |sum, operand| sum.checked_add(*operand)),
+            Semantics::Sub => operands.iter().skip(1).try_fold(operands.first().copied().unwrap_or(0), |difference, operand| {
+                difference.checked_sub(*operand)
+            }),
+            Semantics::Enable | Semantics::Disable | Semantics::Toggle => {
+                self.disabled = match spec.semantics {
+                    Semantics::Enable => false,
+                    Semantics::Disable => true,
+                    _ => !self.disabled,
+                };
+                return if self.disabled { "disabled".to_string() } else { "enabled".to_string() };
+            }
+        };
+        let total = value.and_then(|value| self.total.checked_add(value));
+        let accumulator = match self.disabled {
+            true => Some(self.accumulator),
+            false => value.and_then(|value| self.accumulator.checked_add(value)),
+        };
+        let (Some(value), Some(total), Some(accumulator)) = (value, total, accumulator) else {
+            warn!("{} overflows, ignoring it", instruction.text);
+            return "overflows, ignored".to_string();
+        };
+        self.total = total;
+        self.accumulator = accumulator;
+        if self.disabled {
+            return format!("{value:+} skipped, accumulator {}", self.accumulator);
         }
+        format!("{value:+}, accumulator {}", self.accumulator)
     }
-    filtered_matches
 }
 
 fn main(){
+    env_logger::init();
+
     let args = Cli::parse();
-    let program = get_raw_string(args.file_path);
-    let matches = get_matches(&program);
-    println!("First Answer: {}", add_mults(&matches.values().cloned().collect()));
-    println!("Second Answer: {}",add_mults(&filter_mults(&matches, &get_dos_and_donts(&program)).values().cloned().collect()));
-}
\ No newline at end of file
+    let mut set = InstructionSet::puzzle();
+    for spec in args.instruction {
+        set.register(spec);
+    }
+    let file = File::open(args.file_path).expect("Failed to read file");
+    let mut interpreter = Interpreter::default();
+    for (offset, instruction) in Tokenizer::new(BufReader::new(file), &set, args.chunk_size as usize) {
+        let effect = interpreter.execute(&instruction, &set);
+        if args.trace {
+            println!("{offset:>10}  {:<20} {effect}", instruction.text);
+        }
+        debug!("{offset}: {instruction:?}");
+    }
+    println!("First Answer: {}", interpreter.total);
+    println!("Second Answer: {}", interpreter.accumulator);
+}
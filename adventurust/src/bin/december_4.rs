@@ -1,106 +1,376 @@
-use core::num;
-use std::fs;
-use clap::Parser;
-use itertools::Itertools;
+use std::{collections::HashMap, fs};
+use clap::{Parser, ValueEnum};
 
 
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    file_path: String
+    file_path: String,
+    /// Word to search for, can be given more than once
+    #[arg(long = "word", default_values_t = vec!["XMAS".to_string()])]
+    words: Vec<String>,
+    /// Let words run off one edge of the grid and continue on the opposite edge
+    #[arg(long)]
+    wrap: bool,
+    #[arg(long)]
+    ignore_case: bool,
+    /// Print every match with its start and direction
+    #[arg(long)]
+    list: bool,
+    /// Pattern to count for part two, rows separated by '/'
+    #[arg(long, default_value = "M.S/.A./M.S")]
+    stencil: String,
+    /// Read the stencil from a file instead, one row per line
+    #[arg(long)]
+    stencil_file: Option<String>,
+    /// Character in the stencil that matches any cell
+    #[arg(long, default_value_t = '.')]
+    wildcard: char,
+    /// Only match the stencil as written, not its rotations and reflections
+    #[arg(long)]
+    fixed_orientation: bool,
+    /// Print the grid with the cells of every word match picked out
+    #[arg(long, value_enum)]
+    highlight: Option<HighlightMode>,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum HighlightMode {
+    /// Matched cells in bold colour
+    Ansi,
+    /// Matched cells in upper case and everything else in lower case, for when colour isn't available
+    Lowercase,
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
 
-fn get_raw_string (file_path: String) -> Vec<String> {
-    
-    let read_to_string = fs::read_to_string(file_path).expect("Failed to read file");
-    let raw_data = read_to_string;
+const DIRECTIONS: [Direction; 8] = [
+    Direction::North,
+    Direction::NorthEast,
+    Direction::East,
+    Direction::SouthEast,
+    Direction::South,
+    Direction::SouthWest,
+    Direction::West,
+    Direction::NorthWest,
+];
 
-    let mut grid = Vec::new();
+impl Direction {
+    // (x, y) step with y growing downwards
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+}
 
-    for line in raw_data.lines() {
-        grid.push(line.to_string());
+#[derive(Debug, Clone)]
+struct Grid {
+    cells: Vec<Vec<char>>,
+    width: usize,
+    height: usize,
+}
+
+impl Grid {
+    fn get(&self, x: isize, y: isize, wrap: bool) -> Option<char> {
+        if wrap {
+            let x = x.rem_euclid(self.width as isize) as usize;
+            let y = y.rem_euclid(self.height as isize) as usize;
+            return Some(self.cells[y][x]);
+        }
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some(self.cells[y as usize][x as usize])
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SearchOptions {
+    wrap: bool,
+    ignore_case: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WordMatch {
+    word: String,
+    x: usize,
+    y: usize,
+    direction: Direction,
+}
+
+impl WordMatch {
+    fn cells(&self, grid: &Grid) -> Vec<(usize, usize)> {
+        let (dx, dy) = self.direction.delta();
+        (0..self.word.chars().count() as isize)
+            .map(|i| {
+                let x = (self.x as isize + dx * i).rem_euclid(grid.width as isize) as usize;
+                let y = (self.y as isize + dy * i).rem_euclid(grid.height as isize) as usize;
+                (x, y)
+            })
+            .collect()
     }
-    
-    grid
 }
 
-fn get_all_words(grid: &[String], x: isize, y: isize) -> Vec<String> {
-    let mut words = Vec::new();
-    let xs = [-1, 0, 1];
-    let ys = [-1, 0, 1];
-    
-    for (x_offset, y_offset) in Itertools::cartesian_product(xs.iter(), ys.iter()) {
-        if *x_offset == 0 && *y_offset == 0 {
+fn get_grid (file_path: String) -> Grid {
+
+    let raw_data = fs::read_to_string(file_path).expect("Failed to read file");
+
+    let mut cells = Vec::new();
+    for line in raw_data.lines() {
+        if line.is_empty() {
             continue;
         }
-        let mut x_pos = x;
-        let mut y_pos = y;
-        let mut word = String::new();
-        for _ in 0..4 {
-            if x_pos < 0 || y_pos < 0 || x_pos as usize >= grid[0].len() || y_pos as usize >= grid.len() {
-                break;
-            }
-            word.push(grid[y_pos as usize].chars().nth(x_pos as usize).unwrap());
-            x_pos += x_offset;
-            y_pos += y_offset;
+        cells.push(line.chars().collect::<Vec<char>>());
+    }
+    let width = cells.first().map_or(0, |row| row.len());
+    if cells.iter().any(|row| row.len() != width) {
+        panic!("Grid rows are not all the same width");
+    }
+    Grid { height: cells.len(), width, cells }
+}
+
+fn normalise(c: char, options: SearchOptions) -> char {
+    if options.ignore_case { c.to_ascii_uppercase() } else { c }
+}
+
+fn word_at(grid: &Grid, word: &[char], x: usize, y: usize, direction: Direction, options: SearchOptions) -> bool {
+    let (dx, dy) = direction.delta();
+    for (i, expected) in word.iter().enumerate() {
+        let cell = grid.get(x as isize + dx * i as isize, y as isize + dy * i as isize, options.wrap);
+        match cell {
+            Some(c) if normalise(c, options) == *expected => {}
+            _ => return false,
         }
-        words.push(word);
     }
-    words
+    true
+}
+
+// A palindrome read backwards covers the same cells, and a single letter covers the same cell every way
+fn directions_for(word: &[char]) -> &'static [Direction] {
+    if word.len() == 1 {
+        &[Direction::East]
+    } else if word.iter().eq(word.iter().rev()) {
+        &DIRECTIONS[..4]
+    } else {
+        &DIRECTIONS
+    }
 }
 
-fn is_valid_mas(grid: &[String], x: isize, y: isize) -> bool {
-    if x -1 < 0 || y -1 < 0 || x + 1  >= grid[0].len() as isize|| y +1  >= grid.len() as isize {
-        return false;
+/*
+Words are bucketed by their first letter so each cell only tries the words that could
+start there, every attempt then walks at most the length of the word. That keeps the
+search at grid size * word length per word instead of rebuilding strings at each cell.
+
+A match is a set of cells, so each one is counted once. A palindrome read backwards is the
+same cells as read forwards, so it only tries the first direction of each opposite pair,
+and a single letter only tries East.
+*/
+fn find_words(grid: &Grid, words: &[String], options: SearchOptions) -> Vec<WordMatch> {
+    let mut by_first_letter: HashMap<char, Vec<(&String, Vec<char>)>> = HashMap::new();
+    for word in words {
+        let chars: Vec<char> = word.chars().map(|c| normalise(c, options)).collect();
+        if let Some(first) = chars.first() {
+            by_first_letter.entry(*first).or_default().push((word, chars));
+        }
     }
 
-    let top_left = grid[(y -1) as usize].chars().nth((x - 1) as usize).unwrap();
-    let top_right = grid[(y -1) as usize].chars().nth((x + 1) as usize).unwrap();
-    let bottom_left = grid[(y + 1) as usize].chars().nth((x - 1) as usize).unwrap();
-    let bottom_right = grid[(y + 1) as usize].chars().nth((x + 1) as usize).unwrap();
+    let mut matches = Vec::new();
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            let Some(candidates) = by_first_letter.get(&normalise(grid.cells[y][x], options)) else {
+                continue;
+            };
+            for (word, chars) in candidates {
+                for direction in directions_for(chars).iter().copied() {
+                    if word_at(grid, chars, x, y, direction, options) {
+                        matches.push(WordMatch { word: word.to_string(), x, y, direction });
+                    }
+                }
+            }
+        }
+    }
+    matches
+}
 
-    let diag_1 = format!("{}{}", top_left, bottom_right);
-    let diag_2 = format!("{}{}", top_right, bottom_left);
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Stencil {
+    // None is a wildcard cell
+    cells: Vec<Vec<Option<char>>>,
+    width: usize,
+    height: usize,
+}
 
-    (diag_1 == "MS" || diag_1 == "SM") && (diag_2 == "MS" || diag_2 == "SM")
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StencilMatch {
+    x: usize,
+    y: usize,
+    orientation: String,
 }
-fn count_all_mas_xs(grid: &[String]) -> i32 {
 
-    let mut num_matches = 0;
-    for y in 0..grid.len() {
-        for x in 0..grid[0].len() {
-            if grid[y].chars().nth(x).unwrap() == 'A'{
-                if is_valid_mas(grid, x as isize, y as isize){
-                    num_matches += 1;
+impl Stencil {
+    // Rows separated by '/' or newlines, e.g. "M.S/.A./M.S". It needs at least one cell that isn't a wildcard
+    fn parse(pattern: &str, wildcard: char) -> Result<Stencil, String> {
+        let cells: Vec<Vec<Option<char>>> = pattern
+            .split(['/', '\n'])
+            .filter(|row| !row.is_empty())
+            .map(|row| row.chars().map(|c| if c == wildcard { None } else { Some(c) }).collect())
+            .collect();
+        if !cells.iter().flatten().any(|cell| cell.is_some()) {
+            return Err(format!("Stencil {pattern:?} has no cells to match, only wildcards or nothing at all"));
+        }
+        let width = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        // Pad ragged rows out with wildcards
+        let cells = cells.into_iter().map(|mut row| { row.resize(width, None); row }).collect::<Vec<_>>();
+        Ok(Stencil { height: cells.len(), width, cells })
+    }
+
+    // Quarter turn clockwise
+    fn rotate(&self) -> Stencil {
+        let cells = (0..self.width)
+            .map(|x| (0..self.height).rev().map(|y| self.cells[y][x]).collect())
+            .collect();
+        Stencil { cells, width: self.height, height: self.width }
+    }
+
+    // Mirror left to right
+    fn reflect(&self) -> Stencil {
+        let cells = self.cells.iter().map(|row| row.iter().rev().copied().collect()).collect();
+        Stencil { cells, width: self.width, height: self.height }
+    }
+
+    // All eight rotations and reflections, dropping any that look the same so symmetric shapes aren't counted twice
+    fn orientations(&self) -> Vec<(String, Stencil)> {
+        let mut orientations: Vec<(String, Stencil)> = Vec::new();
+        for (reflected, start) in [(false, self.clone()), (true, self.reflect())] {
+            let mut current = start;
+            for quarter_turns in 0..4 {
+                if !orientations.iter().any(|(_, existing)| *existing == current) {
+                    let name = format!("rotated {}{}", quarter_turns * 90, if reflected { ", reflected" } else { "" });
+                    orientations.push((name, current.clone()));
                 }
+                current = current.rotate();
             }
         }
+        orientations
     }
 
-    num_matches
+    fn matches_at(&self, grid: &Grid, x: usize, y: usize, options: SearchOptions) -> bool {
+        for (dy, row) in self.cells.iter().enumerate() {
+            for (dx, cell) in row.iter().enumerate() {
+                let Some(expected) = cell else {
+                    continue;
+                };
+                match grid.get((x + dx) as isize, (y + dy) as isize, options.wrap) {
+                    Some(c) if normalise(c, options) == normalise(*expected, options) => {}
+                    _ => return false,
+                }
+            }
+        }
+        true
+    }
 }
 
-fn count_all_xmas_matches(grid: &[String]) -> i32 {
-    
-    let mut num_matches = 0;
-    for y in 0..grid.len() {
-        for x in 0..grid[0].len() {
-            if grid[y].chars().nth(x).unwrap() == 'X'{
-                num_matches += get_all_words(grid, x as isize, y as isize).iter().filter(|x| *x == "XMAS").count() as i32;
+// Locations are the top left corner of the stencil
+fn find_stencil(grid: &Grid, stencil: &Stencil, all_orientations: bool, options: SearchOptions) -> Vec<StencilMatch> {
+    let orientations = if all_orientations {
+        stencil.orientations()
+    } else {
+        vec![("rotated 0".to_string(), stencil.clone())]
+    };
+    let mut matches = Vec::new();
+    for (orientation, variant) in orientations.iter() {
+        // Without wrapping the stencil has to fit inside the grid
+        let (max_x, max_y) = if options.wrap {
+            (grid.width, grid.height)
+        } else if variant.width <= grid.width && variant.height <= grid.height {
+            (grid.width - variant.width + 1, grid.height - variant.height + 1)
+        } else {
+            continue;
+        };
+        for y in 0..max_y {
+            for x in 0..max_x {
+                if variant.matches_at(grid, x, y, options) {
+                    matches.push(StencilMatch { x, y, orientation: orientation.clone() });
+                }
             }
         }
     }
-    
-    num_matches
+    matches
 }
 
 
+fn render_highlighted(grid: &Grid, matches: &[WordMatch], mode: HighlightMode) -> String {
+    let mut covered = vec![vec![false; grid.width]; grid.height];
+    for word_match in matches {
+        for (x, y) in word_match.cells(grid) {
+            covered[y][x] = true;
+        }
+    }
+
+    let mut rendered = String::new();
+    for (y, row) in grid.cells.iter().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            match (mode, covered[y][x]) {
+                (HighlightMode::Ansi, true) => rendered += &format!("\x1b[1;32m{c}\x1b[0m"),
+                (HighlightMode::Ansi, false) => rendered.push(*c),
+                (HighlightMode::Lowercase, true) => rendered.push(c.to_ascii_uppercase()),
+                (HighlightMode::Lowercase, false) => rendered.push(c.to_ascii_lowercase()),
+            }
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
 fn main(){
     let args = Cli::parse();
-    let program = get_raw_string(args.file_path);
-    println!("Answer 1: {}", count_all_xmas_matches(&program));
-    println!("Answer 2: {}", count_all_mas_xs(&program));
-}
\ No newline at end of file
+    let pattern = match args.stencil_file {
+        Some(stencil_file) => fs::read_to_string(stencil_file).expect("Failed to read stencil"),
+        None => args.stencil,
+    };
+    let stencil = Stencil::parse(&pattern, args.wildcard).unwrap_or_else(|error| panic!("{error}"));
+    let grid = get_grid(args.file_path);
+    let options = SearchOptions { wrap: args.wrap, ignore_case: args.ignore_case };
+    let matches = find_words(&grid, &args.words, options);
+    if args.list {
+        for word_match in matches.iter() {
+            println!("{} at ({}, {}) going {:?}", word_match.word, word_match.x, word_match.y, word_match.direction);
+        }
+    }
+    if let Some(mode) = args.highlight {
+        print!("{}", render_highlighted(&grid, &matches, mode));
+    }
+    if args.words.len() > 1 || args.highlight.is_some() {
+        for word in args.words.iter() {
+            println!("{word}: {}", matches.iter().filter(|word_match| word_match.word == *word).count());
+        }
+    }
+    println!("Answer 1: {}", matches.len());
+
+    let stencil_matches = find_stencil(&grid, &stencil, !args.fixed_orientation, options);
+    if args.list {
+        for stencil_match in stencil_matches.iter() {
+            println!("Stencil at ({}, {}) {}", stencil_match.x, stencil_match.y, stencil_match.orientation);
+        }
+    }
+    println!("Answer 2: {}", stencil_matches.len());
+}
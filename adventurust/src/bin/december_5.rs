@@ -1,8 +1,8 @@
-use std::{collections::HashMap, fs};
-use clap::Parser;
-use log::{self, debug, info};
-use std::cmp::Ordering;
+use std::{collections::{BTreeSet, HashMap, HashSet}, fs};
+use clap::{Parser, ValueEnum};
+use log::{self, debug, error, info};
 use std::time::Instant;
+use adventurust::runner::{measure, write_bench_json};
 
 
 
@@ -14,32 +14,213 @@ type Rule = (i32, i32);
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    file_path: String
+    /// Not needed with --benchmark, which makes up its own rules
+    #[arg(required_unless_present = "benchmark")]
+    file_path: Option<String>,
+    /// Print the broken rules and the page moves that fix each out of order update
+    #[arg(long)]
+    explain: bool,
+    /// Print the rule graph in this format instead of solving
+    #[arg(long, value_enum)]
+    export: Option<GraphFormat>,
+    /// Only export the pages and rules of this update (1 based, in file order)
+    #[arg(long, requires = "export")]
+    update: Option<usize>,
+    /// Time the indexed rule check against the linear one on generated rules over this many pages
+    #[arg(long)]
+    benchmark: Option<usize>,
+    /// Write the benchmark phase timings to this file as JSON
+    #[arg(long, requires = "benchmark")]
+    bench_json: Option<String>,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+
 
+/*
+The rules are indexed by the page that has to come first, and each update gets a map from
+page to position. Checking an update then only looks at the rules starting from one of its
+own pages, O(pages + relevant rules) instead of scanning every rule against the update.
+*/
+#[derive(Debug, Default)]
+struct RuleIndex {
+    after: HashMap<i32, Vec<i32>>,
+}
 
+impl RuleIndex {
+    fn new(rules: &[Rule]) -> Self {
+        let mut index = RuleIndex::default();
+        for (first, second) in rules {
+            index.after.entry(*first).or_default().push(*second);
+        }
+        index
+    }
+
+    // Calls `broken` with every rule the update breaks, stopping early if it returns false
+    fn for_each_violation(&self, print_command: &PrintCommand, mut broken: impl FnMut(Rule) -> bool) {
+        let positions: HashMap<i32, usize> = print_command.iter().enumerate().map(|(i, page)| (*page, i)).collect();
+        for (position, page) in print_command.iter().enumerate() {
+            for later in self.after.get(page).into_iter().flatten() {
+                if positions.get(later).is_some_and(|later_position| *later_position < position) && !broken((*page, *later)) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// The original check, kept to benchmark the index against
 fn rule_is_satisfied(print_command: &PrintCommand, rule: &Rule) -> Option<bool> {
+    let first = print_command.iter().position(|x| *x == rule.0)?;
+    let second = print_command.iter().position(|x| *x == rule.1)?;
+    Some(first < second)
+}
+
+fn check_print_command_linear(print_command: &PrintCommand, rules: &[Rule]) -> bool {
+    rules.iter().all(|rule| rule_is_satisfied(print_command, rule) != Some(false))
+}
+
+fn check_print_command(print_command: &PrintCommand, index: &RuleIndex) -> bool {
+    let mut valid = true;
+    index.for_each_violation(print_command, |rule| {
+        debug!("Print command {:?} does not satisfy rule {:?}", print_command, rule);
+        valid = false;
+        false
+    });
+    valid
+}
+
+fn violated_rules(print_command: &PrintCommand, index: &RuleIndex) -> Vec<Rule> {
+    let mut violated = Vec::new();
+    index.for_each_violation(print_command, |rule| {
+        violated.push(rule);
+        true
+    });
+    violated
+}
 
-    let first = print_command.iter().position(|x| *x == rule.0);
-    let second = print_command.iter().position(|x| *x == rule.1);
-    if first.is_some() && second.is_some() {
-        let first_value = first.unwrap();
-        let second_value = second.unwrap();
-        return Some(first_value < second_value);
+/*
+The pages that don't need to move are the longest run that already appears in the same
+relative order in both, and since both orders hold the same pages that's the longest
+increasing run of their positions in the fixed order. Everything else moves once.
+*/
+fn pages_to_move(original: &PrintCommand, fixed: &PrintCommand) -> Vec<i32> {
+    let fixed_positions: HashMap<i32, usize> = fixed.iter().enumerate().map(|(i, page)| (*page, i)).collect();
+    let positions: Vec<usize> = original.iter().map(|page| fixed_positions[page]).collect();
+
+    let mut longest = vec![1; positions.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; positions.len()];
+    for i in 0..positions.len() {
+        for j in 0..i {
+            if positions[j] < positions[i] && longest[j] + 1 > longest[i] {
+                longest[i] = longest[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+
+    let mut stays = vec![false; positions.len()];
+    let mut current = (0..positions.len()).max_by_key(|i| longest[*i]);
+    while let Some(i) = current {
+        stays[i] = true;
+        current = previous[i];
     }
-    None
+    original.iter().enumerate().filter(|(i, _)| !stays[*i]).map(|(_, page)| *page).collect()
 }
 
-fn check_print_command(print_command: &PrintCommand, rules: &Vec<Rule>) -> bool {
-    for rule in rules {
-        let rule_met = rule_is_satisfied(print_command, rule);
-        if  rule_met.is_some() && !rule_met.unwrap() {
-            debug!("Print command {:?} does not satisfy rule {:?}", print_command, rule);
-            return false;
+fn format_pages(print_command: &PrintCommand, moved: &[i32]) -> String {
+    print_command
+        .iter()
+        .map(|page| if moved.contains(page) { format!("[{page}]") } else { page.to_string() })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn explain_print_command(print_command: &PrintCommand, rules: &Vec<Rule>, index: &RuleIndex) -> String {
+    let mut explanation = format!("update {}\n", format_pages(print_command, &[]));
+    for rule in violated_rules(print_command, index) {
+        explanation += &format!("  violates {}|{}\n", rule.0, rule.1);
+    }
+    match order_pages(print_command, rules) {
+        Ok(fixed) => {
+            let moved = pages_to_move(print_command, &fixed);
+            explanation += &format!("  - {}\n", format_pages(print_command, &moved));
+            explanation += &format!("  + {}\n", format_pages(&fixed, &moved));
+            // Done in fixed order each page's new neighbour is already in place
+            for (position, page) in fixed.iter().enumerate().filter(|(_, page)| moved.contains(page)) {
+                match position {
+                    0 => explanation += &format!("  move {page} to the front\n"),
+                    _ => explanation += &format!("  move {} after {}\n", page, fixed[position - 1]),
+                }
+            }
         }
+        Err(cycle) => explanation += &format!("  can't be fixed, the rules loop through pages {:?}\n", cycle),
     }
-    true
+    explanation
+}
+
+/*
+Edges point from the page that has to come first. Broken edges are drawn in red, for
+the whole graph that's any rule some update breaks, for a single update only its own.
+*/
+fn export_graph(rules: &[Rule], prints: &[PrintCommand], update: Option<usize>, format: GraphFormat) -> String {
+    let (rules, violated): (Vec<Rule>, HashSet<Rule>) = match update {
+        Some(number) => {
+            let print_command = prints
+                .get(number.wrapping_sub(1))
+                .unwrap_or_else(|| panic!("There is no update {}, the file has {}", number, prints.len()));
+            let rules: Vec<Rule> = rules
+                .iter()
+                .filter(|(first, second)| print_command.contains(first) && print_command.contains(second))
+                .copied()
+                .collect();
+            let violated = violated_rules(print_command, &RuleIndex::new(&rules)).into_iter().collect();
+            (rules, violated)
+        }
+        None => {
+            let index = RuleIndex::new(rules);
+            let violated = prints.iter().flat_map(|print_command| violated_rules(print_command, &index)).collect();
+            (rules.to_vec(), violated)
+        }
+    };
+    let mut pages: BTreeSet<i32> = rules.iter().flat_map(|(first, second)| [*first, *second]).collect();
+    if let Some(number) = update {
+        pages.extend(prints[number - 1].iter());
+    }
+
+    let mut graph = String::new();
+    match format {
+        GraphFormat::Dot => {
+            graph += "digraph rules {\n";
+            for page in pages.iter() {
+                graph += &format!("    {page};\n");
+            }
+            for rule in rules.iter() {
+                let style = if violated.contains(rule) { " [color=red]" } else { "" };
+                graph += &format!("    {} -> {}{};\n", rule.0, rule.1, style);
+            }
+            graph += "}\n";
+        }
+        GraphFormat::Mermaid => {
+            graph += "graph LR\n";
+            for page in pages.iter() {
+                graph += &format!("    {page}\n");
+            }
+            // Mermaid styles edges by their position in the file
+            for (index, rule) in rules.iter().enumerate() {
+                graph += &format!("    {} --> {}\n", rule.0, rule.1);
+                if violated.contains(rule) {
+                    graph += &format!("    linkStyle {index} stroke:red\n");
+                }
+            }
+        }
+    }
+    graph
 }
 
 fn get_print_middle_value(print_command: &PrintCommand) -> i32 {
@@ -47,11 +228,11 @@ fn get_print_middle_value(print_command: &PrintCommand) -> i32 {
     print_command[middle_index]
 }
 
-fn sum_print_commands(print_commands: &Vec<PrintCommand>, rules: &Vec<Rule>) -> i32 {
+fn sum_print_commands(print_commands: &Vec<PrintCommand>, index: &RuleIndex) -> i32 {
     let mut sum = 0;
     let mut num_valid_print_commands = 0;
     for print_command in print_commands {
-        if check_print_command(print_command, rules) {
+        if check_print_command(print_command, index) {
             sum += get_print_middle_value(print_command);
             num_valid_print_commands += 1;
         }
@@ -61,55 +242,71 @@ fn sum_print_commands(print_commands: &Vec<PrintCommand>, rules: &Vec<Rule>) ->
 }
 
 
-fn sort_rules_by_print_command_order(print_command: &PrintCommand, rules: &Vec<Rule>) -> Vec<Rule> {
-    let mut new_rules = rules.clone();
-    
-    new_rules.sort_by(|a, b| {
-        let a_index = print_command.iter().position(|x| *x == a.0).unwrap();
-        let b_index = print_command.iter().position(|x| *x == b.0).unwrap();
-        a_index.cmp(&b_index)
-    });
-
-    new_rules
-}
-
-fn fix_print_command(print_command: &PrintCommand, rules: &Vec<Rule>) -> PrintCommand {
-    let mut new_print_command = print_command.clone();
-
-    let mut relevant_rules = rules.clone();
-    relevant_rules.retain(|rule| rule_is_satisfied(print_command, rule).is_some());
-    relevant_rules = sort_rules_by_print_command_order(print_command, &relevant_rules);
-    debug!("Relevant rules: {:?} for command {:?}", relevant_rules, print_command);
-
-    
-
+/*
+The rules restricted to the pages of one update form a small directed graph, and a
+correct order is a topological sort of it. Kahn's algorithm: repeatedly take a page
+nothing else needs to come before, breaking ties by where the page was in the update
+so already ordered updates come back unchanged.
+
+If pages are left over they all still have a predecessor among themselves, so walking
+backwards through predecessors has to revisit a page, and that loop is the cycle.
+*/
+fn order_pages(print_command: &PrintCommand, rules: &Vec<Rule>) -> Result<PrintCommand, Vec<i32>> {
+    let positions: HashMap<i32, usize> = print_command.iter().enumerate().map(|(i, page)| (*page, i)).collect();
+    let mut successors: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut predecessors: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut in_degree = vec![0; print_command.len()];
     for rule in rules {
-        let rule_met = rule_is_satisfied(&new_print_command, rule);
-        if  rule_met.is_some() && !rule_met.unwrap() {
-            debug!("Print command {:?} does not satisfy rule {:?}", new_print_command, rule);
-            let first_index = new_print_command.iter().position(|x| *x == rule.1).unwrap();
-            let second_index = new_print_command.iter().position(|x| *x == rule.0).unwrap();
-            new_print_command.remove(second_index);
-            new_print_command.insert(first_index,rule.0);
-            debug!("Print command fixed to {:?}", new_print_command);       
+        if let (Some(before), Some(after)) = (positions.get(&rule.0), positions.get(&rule.1)) {
+            successors.entry(*before).or_default().push(*after);
+            predecessors.entry(*after).or_default().push(*before);
+            in_degree[*after] += 1;
         }
     }
-    if !check_print_command(&new_print_command, &rules){
-        return fix_print_command(&new_print_command,  &relevant_rules)
+
+    let mut ready: BTreeSet<usize> = (0..print_command.len()).filter(|i| in_degree[*i] == 0).collect();
+    let mut ordered = Vec::with_capacity(print_command.len());
+    while let Some(current) = ready.pop_first() {
+        ordered.push(print_command[current]);
+        for next in successors.get(&current).into_iter().flatten() {
+            in_degree[*next] -= 1;
+            if in_degree[*next] == 0 {
+                ready.insert(*next);
+            }
+        }
     }
-    new_print_command
+
+    if ordered.len() == print_command.len() {
+        return Ok(ordered);
+    }
+
+    let mut current = (0..print_command.len()).find(|i| in_degree[*i] > 0).unwrap();
+    let mut seen = Vec::new();
+    while !seen.contains(&current) {
+        seen.push(current);
+        current = *predecessors[&current].iter().find(|before| in_degree[**before] > 0).unwrap();
+    }
+    // We walked backwards, so flip it to read in rule order
+    let start = seen.iter().position(|i| *i == current).unwrap();
+    Err(seen[start..].iter().rev().map(|i| print_command[*i]).collect())
 }
 
-fn fixed_print_commands(print_commands: & Vec<PrintCommand>, rules: &Vec<Rule>) -> Vec<PrintCommand> {
+fn fixed_print_commands(print_commands: & Vec<PrintCommand>, rules: &Vec<Rule>, index: &RuleIndex) -> Vec<PrintCommand> {
     let mut fixed_print_commands = Vec::new();
     for print_command in print_commands {
-        if !check_print_command(print_command, rules) {
-            debug!("Print command {:?} does not satisfy rules", print_command);
-            fixed_print_commands.push(fix_print_command(print_command, rules));
-            debug!("Fixed print command {:?}", fixed_print_commands.last().unwrap());
-
+        if check_print_command(print_command, index) {
+            continue;
+        }
+        match order_pages(print_command, rules) {
+            Ok(fixed) => {
+                debug!("Fixed print command {:?} to {:?}", print_command, fixed);
+                fixed_print_commands.push(fixed);
+            }
+            Err(cycle) => {
+                error!("Print command {:?} can't be ordered, the rules loop through pages {:?}", print_command, cycle);
+            }
         }
-    } 
+    }
     fixed_print_commands
 }
 
@@ -139,57 +336,111 @@ fn get_rules_and_prints (file_path: String) -> (Vec<Rule>, Vec<PrintCommand>) {
 }
 
 
-fn comparator_approach(rules: &Vec<Rule>, prints: &Vec<PrintCommand>) -> i32 {
-    let mut rules_map = HashMap::<i32, std::collections::HashSet<i32>>::new();
-    for rule in rules {
-        if !rules_map.contains_key(&rule.0) {
-            rules_map.insert(rule.0, std::collections::HashSet::new());
-        }
-        rules_map.get_mut(&rule.0).unwrap().insert(rule.1);
-    }   
+// Small LCG so the generated benchmark is the same every run
+struct Lcg(u64);
 
-    let mut sum = 0;
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
 
-    for print_command in prints {
-        if !check_print_command(print_command, rules) {
-            debug!("Print command {:?} does not satisfy rules", print_command);
-            let mut fixed_print = print_command.clone();
-            fixed_print.sort_by(|a, b| {
-                if rules_map.contains_key(&a) {
-                    if rules_map.get(&a).unwrap().contains(&b){
-                        return Ordering::Less;
-                    } else {
-                        return Ordering::Greater;
-                    }
-                } else{
-                    return Ordering::Less;
-                }
-            });
-            debug!("Fixed print command {:?}", fixed_print);
-            sum += get_print_middle_value(&fixed_print);
+/*
+Rules only ever point from a lower page to a higher one so they can't loop, about two thirds
+of all such pairs are kept. Half the updates are left in page order and are valid, the rest
+are shuffled, so the linear check can't just bail out on the first rule every time.
+*/
+fn generate_benchmark(num_pages: usize) -> (Vec<Rule>, Vec<PrintCommand>) {
+    let mut rng = Lcg(num_pages as u64);
+    let pages: Vec<i32> = (10..10 + num_pages as i32).collect();
+    let mut rules = Vec::new();
+    for (i, first) in pages.iter().enumerate() {
+        for second in pages[i + 1..].iter() {
+            if rng.next(3) != 0 {
+                rules.push((*first, *second));
+            }
         }
-    }   
+    }
+    // Shuffle the rule order too, the real input isn't sorted
+    for i in (1..rules.len()).rev() {
+        rules.swap(i, rng.next(i + 1));
+    }
 
-    sum
+    let update_len = 23.min(num_pages);
+    let mut prints = Vec::new();
+    for update in 0..200 {
+        let mut print_command: PrintCommand = Vec::new();
+        while print_command.len() < update_len {
+            let page = pages[rng.next(num_pages)];
+            if !print_command.contains(&page) {
+                print_command.push(page);
+            }
+        }
+        if update % 2 == 0 {
+            print_command.sort();
+        }
+        prints.push(print_command);
+    }
+    (rules, prints)
 }
 
+fn run_benchmark(num_pages: usize, bench_json: Option<String>) {
+    let (rules, prints) = generate_benchmark(num_pages);
+    info!("Generated {} rules over {} pages and {} updates", rules.len(), num_pages, prints.len());
+    let mut phases = Vec::new();
 
+    let (linear, report) = measure("linear", || {
+        prints.iter().map(|print_command| check_print_command_linear(print_command, &rules)).collect::<Vec<bool>>()
+    });
+    println!("{report}");
+    phases.push(report);
 
+    let (index, report) = measure("build index", || RuleIndex::new(&rules));
+    println!("{report}");
+    phases.push(report);
+
+    let (indexed, report) = measure("indexed", || {
+        prints.iter().map(|print_command| check_print_command(print_command, &index)).collect::<Vec<bool>>()
+    });
+    println!("{report}");
+    phases.push(report);
+
+    if linear != indexed {
+        panic!("The indexed check disagrees with the linear one");
+    }
+    println!("Valid updates: {} out of {}", indexed.iter().filter(|valid| **valid).count(), prints.len());
+    if let Some(bench_json) = bench_json {
+        write_bench_json(&bench_json, "december_5", &phases);
+    }
+}
 
 fn main(){
     env_logger::init();
 
     let args = Cli::parse();
-    let (rules, prints) = get_rules_and_prints(args.file_path);
-    let sum = sum_print_commands(&prints, &rules);
-    info!("Answer 1: {sum}");
+    if let Some(num_pages) = args.benchmark {
+        run_benchmark(num_pages, args.bench_json);
+        return;
+    }
+    let (rules, prints) = get_rules_and_prints(args.file_path.expect("A file path is required without --benchmark"));
+    let index = RuleIndex::new(&rules);
+    if let Some(format) = args.export {
+        print!("{}", export_graph(&rules, &prints, args.update, format));
+        return;
+    }
+    if args.explain {
+        for print_command in prints.iter().filter(|print_command| !check_print_command(print_command, &index)) {
+            print!("{}", explain_print_command(print_command, &rules, &index));
+        }
+        return;
+    }
+    let sum = sum_print_commands(&prints, &index);
+    println!("Answer 1: {sum}");
 
     let before = Instant::now();
-    let fixed_prints = fixed_print_commands(&prints, &rules);   
-    let fixed_sum = sum_print_commands(&fixed_prints, &rules);
-    info!("Answer 2: {fixed_sum} Elapsed time: {:.2?}", before.elapsed());
-
-    let before2 = Instant::now();
-    let comparator_sum = comparator_approach(&rules, &prints);
-    info!("Answer 2 -- better: {comparator_sum} Elapsed time: {:.2?}", before2.elapsed());
+    let fixed_prints = fixed_print_commands(&prints, &rules, &index);
+    let fixed_sum = sum_print_commands(&fixed_prints, &index);
+    println!("Answer 2: {fixed_sum}");
+    info!("Elapsed time: {:.2?}", before.elapsed());
 }
\ No newline at end of file
@@ -1,316 +1,476 @@
-use clap::Parser;
-use enum_map::{enum_map, Enum, EnumMap};
+use adventurust::runner::{measure, write_bench_json};
+use adventurust::progress::Progress;
+use adventurust::snapshot;
+use clap::{Parser, ValueEnum};
 use itertools::Itertools;
 use log::{self, debug, info};
-use std::{collections::HashMap, fs, collections::HashSet};
-
-#[derive(Debug, Enum, Copy, Clone, PartialEq, Eq, Hash)]
-enum MapType {
-    Empty,
-    VisitedVertical,
-    VisitedHorizontal,
-    VisitedBoth,
-    Obstruction,
-    GuardUp,
-    GuardDown,
-    GuardLeft,
-    GuardRight,
+use std::{collections::HashSet, fs};
+
+/*
+The lab layout never changes once it's parsed, so it's kept apart from anything a walk
+changes: a guard is just a position and a direction and is cheap to copy, and the route
+it takes is drawn on a separate overlay. Any number of walks can then share one `Lab`,
+each trying its own extra obstruction without touching the layout.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
 }
 
-#[derive(Debug, Clone)]
-struct MapState {
-    map: Map,
-    guard_position: (usize, usize),
-    guard_type: MapType,
-    guard_states: HashSet<(MapType, (usize, usize))>,
-    prior_space_state: MapType,
-    map_size: (usize, usize),
-    guard_step: EnumMap<MapType, (i8, i8)>,
-    map_string: EnumMap<MapType, char>,
-    valid_moves: EnumMap<MapType, MapType>,
-    guard_present: bool,
-    all_turns: HashMap<MapType, Vec<(usize, usize)>>,
-    number_of_loops: i32,
+// Clockwise, so turning right is the next direction along
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+impl Direction {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    // (row, column) step
+    fn step(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Right => (0, 1),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+        }
+    }
+
+    fn turn(self, rule: TurnRule) -> Direction {
+        let quarter_turns = match rule {
+            TurnRule::Right => 1,
+            TurnRule::Reverse => 2,
+            TurnRule::Left => 3,
+        };
+        DIRECTIONS[(self.index() + quarter_turns) % 4]
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
+
+    fn glyph(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
 }
 
-type Map = Vec<Vec<MapType>>;
+// What a guard does when it walks into an obstruction
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum TurnRule {
+    Right,
+    Left,
+    Reverse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Guard {
+    position: (usize, usize),
+    direction: Direction,
+}
+
+// Stop value for a guard that walks off the map
+const EXIT: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+struct Lab {
+    width: usize,
+    height: usize,
+    obstructions: Vec<bool>,
+    // For every direction and cell, the cell the guard stops on in front of the next obstruction
+    stops: [Vec<u32>; 4],
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
     file_path: String,
+    /// Write phase timings and heap usage to this file as JSON
+    #[arg(long)]
+    bench_json: Option<String>,
+    /// Compare the final map against the stored snapshot with this name
+    #[arg(long)]
+    snapshot: Option<String>,
+    /// Overwrite the stored snapshot instead of comparing against it
+    #[arg(long)]
+    update_snapshots: bool,
+    /// List where an obstruction makes the guard loop, and show them all as O on the guard's path
+    #[arg(long)]
+    list_obstructions: bool,
+    /// Draw the loop each obstruction causes, like the illustrations in the puzzle
+    #[arg(long)]
+    render_loops: bool,
+    /// Which way the guards turn when they hit an obstruction
+    #[arg(long, value_enum, default_value_t = TurnRule::Right)]
+    turn: TurnRule,
 }
 
-fn parse_map(file_path: String) -> Map {
+// Any number of guards, in reading order
+fn parse_lab(file_path: String) -> (Lab, Vec<Guard>) {
     let contents = fs::read_to_string(file_path).expect("Should have been able to read the file");
-    let lines = contents.split("\n");
-    let mut map = Vec::new();
-    for line in lines {
-        let mut row = Vec::new();
-        for c in line.chars() {
-            row.push(match c {
-                '.' => MapType::Empty,
-                '#' => MapType::Obstruction,
-                '^' => MapType::GuardUp,
-                'v' => MapType::GuardDown,
-                '<' => MapType::GuardLeft,
-                '>' => MapType::GuardRight,
+    let mut obstructions = Vec::new();
+    let mut guards = Vec::new();
+    let mut width = 0;
+    for (i, line) in contents.lines().filter(|line| !line.is_empty()).enumerate() {
+        width = line.len();
+        for (j, c) in line.chars().enumerate() {
+            let direction = match c {
+                '.' | '#' => None,
+                '^' => Some(Direction::Up),
+                'v' => Some(Direction::Down),
+                '<' => Some(Direction::Left),
+                '>' => Some(Direction::Right),
                 _ => panic!("Invalid"),
-            })
+            };
+            if let Some(direction) = direction {
+                guards.push(Guard { position: (i, j), direction });
+            }
+            obstructions.push(c == '#');
         }
-        map.push(row);
     }
-    map
-}
-
-fn print_map(map: &Map, map_string: EnumMap<MapType, char>) {
-    for row in map {
-        for cell in row {
-            print!("{}", map_string[*cell]);
-        }
-        println!();
+    if width == 0 || obstructions.len() % width != 0 {
+        panic!("Map rows are not all the same width");
     }
+    let lab = Lab::new(width, obstructions.len() / width, obstructions);
+    (lab, guards)
 }
 
-fn check_if_potential_loop_old(map: &mut MapState) {
-    let current_position = map.guard_position;
-
-    for (turn_type, turn_place) in map.all_turns.iter() {
-        for turn in turn_place {
-            match (map.guard_type, turn_type) {
-                (MapType::GuardUp, MapType::GuardDown) => {
-                    if current_position.0 == turn.0 && turn.1 >= current_position.1 {
-                        map.number_of_loops += 1;
-                    }
-                }
-                (MapType::GuardDown, MapType::GuardUp) => {
-                    if current_position.0 == turn.0 && turn.1 <= current_position.1 {
-                        map.number_of_loops += 1;
-                    }
-                }
-                (MapType::GuardLeft, MapType::GuardRight) => {
-                    if current_position.1 == turn.1 && turn.0 <= current_position.0 {
-                        map.number_of_loops += 1;
-                    }
-                }
-                (MapType::GuardRight, MapType::GuardLeft) => {
-                    if current_position.1 == turn.1 && turn.0 >= current_position.0 {
-                        map.number_of_loops += 1;
+impl Lab {
+    // Each direction is filled in walking against it, so a cell's neighbour is always done first
+    fn new(width: usize, height: usize, obstructions: Vec<bool>) -> Self {
+        let mut stops: [Vec<u32>; 4] = std::array::from_fn(|_| vec![EXIT; width * height]);
+        for direction in DIRECTIONS {
+            let (dr, dc) = direction.step();
+            let rows: Vec<usize> = if dr > 0 { (0..height).rev().collect() } else { (0..height).collect() };
+            let cols: Vec<usize> = if dc > 0 { (0..width).rev().collect() } else { (0..width).collect() };
+            for r in rows.iter() {
+                for c in cols.iter() {
+                    let next = (*r as isize + dr, *c as isize + dc);
+                    if next.0 < 0 || next.1 < 0 || next.0 as usize >= height || next.1 as usize >= width {
+                        continue;
                     }
+                    let next = next.0 as usize * width + next.1 as usize;
+                    let stops = &mut stops[direction.index()];
+                    stops[r * width + c] = if obstructions[next] { (r * width + c) as u32 } else { stops[next] };
                 }
-                _ => {}
             }
         }
+        Lab { width, height, obstructions, stops }
     }
-}
 
-fn check_if_potential_loop(original_map: &MapState) -> bool {
-    let mut map = original_map.clone();
-    let guard_step = map.guard_step[map.guard_type];
-    let new_position = (
-        map.guard_position.0 as i32 + guard_step.0 as i32,
-        map.guard_position.1 as i32 + guard_step.1 as i32,
-    );
-    
-    if !bounds_check(new_position, map.map_size) || 
-        map.map[new_position.0 as usize][new_position.1 as usize] == MapType::Obstruction {
-        return false
+    fn num_cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn cell(&self, position: (usize, usize)) -> usize {
+        position.0 * self.width + position.1
+    }
+
+    fn position(&self, cell: usize) -> (usize, usize) {
+        (cell / self.width, cell % self.width)
     }
-    //map.guard_states = HashSet::new();
-    map.map[new_position.0 as usize][new_position.1 as usize] = MapType::Obstruction;
-
-    debug!("Simulating {:?} {:?}", map.guard_position, map.guard_type);
-    while map.guard_present {
-        map_step(&mut map, false);
-        if map.guard_states.contains(&(map.guard_type, map.guard_position)){
-            debug!("Loop detected {:?} {:?}", map.guard_position, map.guard_type);
-            return true
-        }
-    } 
 
-    false
+    /*
+    Where the guard stops with one extra obstruction on the map. The table is left alone,
+    the extra cell only matters if it's on the ray the guard is about to walk and no further
+    than the stop the table gives, in which case the guard stops just in front of it.
+    */
+    fn stop(&self, guard: Guard, extra: Option<(usize, usize)>) -> u32 {
+        let stop = self.stops[guard.direction.index()][self.cell(guard.position)];
+        let Some(extra) = extra else {
+            return stop;
+        };
+        let (r, c) = (guard.position.0 as isize, guard.position.1 as isize);
+        let (er, ec) = (extra.0 as isize, extra.1 as isize);
+        let (dr, dc) = guard.direction.step();
+        // How many steps along the ray the extra obstruction is, if it's on it at all
+        let distance = match (dr, dc) {
+            (0, _) if er == r => (ec - c) * dc,
+            (_, 0) if ec == c => (er - r) * dr,
+            _ => return stop,
+        };
+        let stop_distance = if stop == EXIT {
+            isize::MAX
+        } else {
+            let (sr, sc) = self.position(stop as usize);
+            (sr as isize - r) * dr + (sc as isize - c) * dc
+        };
+        if distance <= 0 || distance > stop_distance {
+            return stop;
+        }
+        self.cell(((er - dr) as usize, (ec - dc) as usize)) as u32
+    }
 }
 
+// One bit per cell and direction the guard can be facing
+#[derive(Debug, Clone)]
+struct StateSet {
+    bits: Vec<u64>,
+}
 
-fn get_visit_type(guard_type: MapType, prior_state: MapType) -> MapType {
-    if prior_state == MapType::VisitedVertical && (guard_type == MapType::GuardRight || guard_type == MapType::GuardLeft) {
-        return MapType::VisitedBoth;
+impl StateSet {
+    fn new(num_cells: usize) -> Self {
+        StateSet { bits: vec![0; (num_cells * 4).div_ceil(64)] }
     }
-    if prior_state == MapType::VisitedHorizontal && (guard_type == MapType::GuardUp || guard_type == MapType::GuardDown) {
-        return MapType::VisitedBoth;
+
+    // True if the state wasn't already in the set
+    fn insert(&mut self, cell: usize, direction: Direction) -> bool {
+        let bit = cell * 4 + direction.index();
+        let mask = 1 << (bit % 64);
+        let new = self.bits[bit / 64] & mask == 0;
+        self.bits[bit / 64] |= mask;
+        new
     }
-    if prior_state == MapType::VisitedBoth {
-        return MapType::VisitedBoth;
+
+    fn clear(&mut self) {
+        self.bits.fill(0);
     }
-    match guard_type {
-        MapType::GuardUp => MapType::VisitedVertical,
-        MapType::GuardDown => MapType::VisitedVertical,
-        MapType::GuardLeft => MapType::VisitedHorizontal,
-        MapType::GuardRight => MapType::VisitedHorizontal,
-        _ => panic!("Invalid guard type"),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    Unvisited,
+    Vertical,
+    Horizontal,
+    Both,
+}
+
+impl Visit {
+    fn glyph(self) -> char {
+        match self {
+            Visit::Unvisited => '.',
+            Visit::Vertical => '|',
+            Visit::Horizontal => '-',
+            Visit::Both => '+',
+        }
     }
 }
 
-fn map_step(map: &mut MapState, simulate: bool) -> &MapState {
-    let guard_step = map.guard_step[map.guard_type];
-    let guard_position = map.guard_position;
-    let new_position = (
-        guard_position.0 as i32 + guard_step.0 as i32,
-        guard_position.1 as i32 + guard_step.1 as i32,
-    );
-
-    if !bounds_check(new_position, map.map_size) {
-        map.map[guard_position.0][guard_position.1] = get_visit_type(map.guard_type, map.prior_space_state);
-        map.guard_position = (new_position.0 as usize, new_position.1 as usize);
-        map.guard_present = false;
-        return map;
+// The route a guard took, drawn over a lab without changing it
+#[derive(Debug, Clone)]
+struct Visits {
+    cells: Vec<Visit>,
+}
+
+impl Visits {
+    fn new(lab: &Lab) -> Self {
+        Visits { cells: vec![Visit::Unvisited; lab.num_cells()] }
+    }
+
+    fn pass(&mut self, cell: usize, direction: Direction) {
+        let along = if direction.is_vertical() { Visit::Vertical } else { Visit::Horizontal };
+        self.cells[cell] = match self.cells[cell] {
+            Visit::Unvisited => along,
+            visit if visit == along => visit,
+            _ => Visit::Both,
+        };
     }
 
-    map.guard_states.insert((map.guard_type, map.guard_position));
+    // Corners are drawn as crossings, same as in the puzzle
+    fn turn(&mut self, cell: usize) {
+        self.cells[cell] = Visit::Both;
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        (0..self.cells.len()).filter(|cell| self.cells[*cell] != Visit::Unvisited).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    // The last place the guard stood before leaving the map
+    Exits(Guard),
+    // Cells walked and turns made going once round the loop
+    Loops { steps: usize, turns: usize },
+}
 
-    let new_position_state = map.map[new_position.0 as usize][new_position.1 as usize];
-    match new_position_state {
-        MapType::Empty => {
-            map.guard_position = (new_position.0 as usize, new_position.1 as usize);         
-            map.map[guard_position.0 as usize][guard_position.1 as usize] = get_visit_type(map.guard_type, map.prior_space_state);
-            map.map[new_position.0 as usize][new_position.1 as usize] = map.guard_type;
-            map.prior_space_state = MapType::Empty;
+fn steps_between(lab: &Lab, from: (usize, usize), to: usize) -> usize {
+    let to = lab.position(to);
+    from.0.abs_diff(to.0) + from.1.abs_diff(to.1)
+}
+
+// Goes round once more from a turn we know repeats, counting as it goes
+fn measure_cycle(lab: &Lab, repeated: Guard, rule: TurnRule, extra: Option<(usize, usize)>) -> Outcome {
+    let (mut steps, mut turns) = (0, 0);
+    let mut guard = repeated;
+    loop {
+        guard = Guard { direction: guard.direction.turn(rule), ..guard };
+        turns += 1;
+        let stop = lab.stop(guard, extra) as usize;
+        steps += steps_between(lab, guard.position, stop);
+        guard = Guard { position: lab.position(stop), ..guard };
+        if guard == repeated {
+            return Outcome::Loops { steps, turns };
         }
-        MapType::VisitedVertical | MapType::VisitedHorizontal | MapType::VisitedBoth=> {
-            map.guard_position = (new_position.0 as usize, new_position.1 as usize);
-            map.map[guard_position.0][guard_position.1] = get_visit_type(map.guard_type, map.prior_space_state);
-            map.map[new_position.0 as usize][new_position.1 as usize] = map.guard_type;
-            map.prior_space_state = new_position_state;
+    }
+}
+
+/*
+Walks the guard from turn to turn using the lab's stop tables. It's in a loop once it turns
+at the same place facing the same way twice. `turns` is passed in so repeated walks can
+reuse it, and the route is only drawn when there's an overlay to draw it on.
+*/
+fn walk(lab: &Lab, mut guard: Guard, rule: TurnRule, extra: Option<(usize, usize)>, turns: &mut StateSet, mut visits: Option<&mut Visits>) -> Outcome {
+    turns.clear();
+    loop {
+        let stop = lab.stop(guard, extra);
+        if let Some(visits) = visits.as_deref_mut() {
+            let (dr, dc) = guard.direction.step();
+            let (mut r, mut c) = (guard.position.0 as isize, guard.position.1 as isize);
+            while r >= 0 && c >= 0 && (r as usize) < lab.height && (c as usize) < lab.width {
+                let cell = lab.cell((r as usize, c as usize));
+                visits.pass(cell, guard.direction);
+                if cell as u32 == stop {
+                    break;
+                }
+                (r, c) = (r + dr, c + dc);
+            }
         }
-        MapType::Obstruction => {
-            map.prior_space_state =  if map.guard_type == MapType::GuardUp || map.guard_type == MapType::GuardDown {
-                MapType::VisitedVertical
-            } else {
-                MapType::VisitedHorizontal
+        if stop == EXIT {
+            let (dr, dc) = guard.direction.step();
+            let (r, c) = (guard.position.0 as isize, guard.position.1 as isize);
+            // Last cell before the edge along the direction of travel
+            let steps = match (dr, dc) {
+                (-1, _) => r,
+                (1, _) => lab.height as isize - 1 - r,
+                (_, -1) => c,
+                _ => lab.width as isize - 1 - c,
             };
-            
-            map.guard_type = map.valid_moves[map.guard_type];
-            map.map[guard_position.0 as usize][guard_position.1 as usize] = get_visit_type(map.guard_type, map.prior_space_state);
-            map.all_turns
-                .get_mut(&map.guard_type)
-                .unwrap()
-                .push(guard_position);
-            
+            let position = ((r + dr * steps) as usize, (c + dc * steps) as usize);
+            return Outcome::Exits(Guard { position, ..guard });
         }
-        _ => {
-            panic!("Invalid move {:?} at {:?}", new_position_state, new_position);
+        let cell = stop as usize;
+        if let Some(visits) = visits.as_deref_mut() {
+            visits.turn(cell);
         }
-    }
-
-    if simulate {
-        if check_if_potential_loop(map){
-            map.number_of_loops += 1;
+        guard = Guard { position: lab.position(cell), ..guard };
+        if !turns.insert(cell, guard.direction) {
+            return measure_cycle(lab, guard, rule, extra);
         }
+        guard = Guard { direction: guard.direction.turn(rule), ..guard };
     }
-    map
 }
 
-fn bounds_check(new_position: (i32, i32), map_size: (usize, usize)) -> bool {
-    if new_position.0 >= 0
-        && new_position.0 < map_size.0 as i32
-        && new_position.1 >= 0
-        && new_position.1 < map_size.1 as i32
-    {
-        return true;
+/*
+An obstruction can only change the guard's route if it's somewhere on the original path,
+so each cell of that path except the start is tried once, on the untouched lab with the
+guard back at the start. Blocking a cell part way along can change how the guard got there,
+which is why we don't start from wherever the guard was when it first reached it.
+
+With several guards the path is all of their routes, and an obstruction counts if it
+traps any guard that would otherwise have got out. Guards don't block each other.
+*/
+fn loop_obstructions(lab: &Lab, path: &[(usize, usize)], guards: &[Guard], rule: TurnRule) -> HashSet<(usize, usize)> {
+    let mut turns = StateSet::new(lab.num_cells());
+    let escaping: Vec<Guard> = guards
+        .iter()
+        .filter(|guard| matches!(walk(lab, **guard, rule, None, &mut turns, None), Outcome::Exits(_)))
+        .copied()
+        .collect();
+    let mut obstructions = HashSet::new();
+    let mut progress = Progress::new("Obstructions tried", Some(path.len() as u64));
+    for candidate in path.iter().filter(|candidate| guards.iter().all(|guard| guard.position != **candidate)) {
+        let traps = |guard: &Guard| matches!(walk(lab, *guard, rule, Some(*candidate), &mut turns, None), Outcome::Loops { .. });
+        if escaping.iter().any(traps) {
+            debug!("Obstruction at {:?} makes a loop", candidate);
+            obstructions.insert(*candidate);
+        }
+        progress.inc(1);
     }
-    false
+    progress.finish();
+    obstructions
 }
 
-fn find_guard_position(map: &Map) -> (usize, usize) {
-    for (i, row) in map.iter().enumerate() {
-        for (j, cell) in row.iter().enumerate() {
-            if *cell == MapType::GuardUp
-                || *cell == MapType::GuardDown
-                || *cell == MapType::GuardLeft
-                || *cell == MapType::GuardRight
-            {
-                return (i, j);
-            }
+// Placed obstructions are drawn as O, and guards are drawn where they start
+fn render_map(lab: &Lab, visits: &Visits, guards: &[Guard], placed: &[(usize, usize)]) -> String {
+    let mut rendered = String::new();
+    for i in 0..lab.height {
+        for j in 0..lab.width {
+            let cell = lab.cell((i, j));
+            let guard = guards.iter().find(|guard| guard.position == (i, j));
+            rendered.push(match guard {
+                _ if placed.contains(&(i, j)) => 'O',
+                _ if lab.obstructions[cell] => '#',
+                Some(guard) => guard.direction.glyph(),
+                None => visits.cells[cell].glyph(),
+            });
         }
+        rendered.push('\n');
     }
-    panic!("No guard found");
+    rendered
 }
 
 fn main() {
     env_logger::init();
 
-    let map_string: EnumMap<MapType, char> = enum_map! {
-        MapType::Empty => '.',
-        MapType::VisitedVertical => '|',
-        MapType::VisitedHorizontal => '-',
-        MapType::VisitedBoth => '+',
-        MapType::Obstruction => '#',
-        MapType::GuardUp => '^',
-        MapType::GuardDown => 'v',
-        MapType::GuardLeft => '<',
-        MapType::GuardRight => '>',
-    };
-
-    let valid_moves: EnumMap<MapType, MapType> = enum_map! {
-        MapType::Empty => MapType::Empty,
-        MapType::VisitedVertical => MapType::VisitedVertical,
-        MapType::VisitedHorizontal => MapType::VisitedHorizontal,
-        MapType::VisitedBoth => MapType::VisitedBoth,
-        MapType::Obstruction => MapType::Obstruction,
-        MapType::GuardUp => MapType::GuardRight,
-        MapType::GuardDown => MapType::GuardLeft,
-        MapType::GuardLeft => MapType::GuardUp,
-        MapType::GuardRight => MapType::GuardDown,
-    };
-
-    let guard_step: EnumMap<MapType, (i8, i8)> = enum_map! {
-        MapType::Empty => (0, 0),
-        MapType::VisitedVertical => (0, 0),
-        MapType::VisitedHorizontal => (0, 0),
-        MapType::VisitedBoth => (0, 0),
-        MapType::Obstruction => (0, 0),
-        MapType::GuardUp => (-1, 0),
-        MapType::GuardDown => (1, 0),
-        MapType::GuardLeft => (0, -1),
-        MapType::GuardRight => (0, 1),
-    };
-
     let args = Cli::parse();
-    let map = parse_map(args.file_path);
-    let initial_guard_position = find_guard_position(&map);
-    let initial_guard_type = map[initial_guard_position.0][initial_guard_position.1];
-    let map_size = (map.len(), map[0].len());
-    let mut all_turns = HashMap::new();
-    all_turns.insert(MapType::GuardDown, Vec::new());
-    all_turns.insert(MapType::GuardLeft, Vec::new());
-    all_turns.insert(MapType::GuardUp, Vec::new());
-    all_turns.insert(MapType::GuardRight, Vec::new());
-    let guard_states = HashSet::new();
-    let mut map_state = MapState {
-        map: map,
-        map_size: map_size,
-        guard_position: initial_guard_position,
-        guard_states: guard_states,
-        prior_space_state: MapType::Empty,
-        guard_type: initial_guard_type,
-        guard_step: guard_step,
-        map_string: map_string,
-        valid_moves: valid_moves,
-        guard_present: true,
-        number_of_loops: 0,
-        all_turns: all_turns,
-    };
-    info!("Guard found at: {:?}", map_state.guard_position);
-
-    while map_state.guard_present {
-        map_step(&mut map_state, true);
-        debug!("Guard position: {:?}", map_state.guard_position);
+    let mut phases = Vec::new();
+    let ((lab, guards), report) = measure("parse", || parse_lab(args.file_path.clone()));
+    info!("{report}");
+    phases.push(report);
+    if guards.is_empty() {
+        info!("No guards on the map");
+    }
+
+    let mut turns = StateSet::new(lab.num_cells());
+    let mut visits = Visits::new(&lab);
+    let (outcomes, report) = measure("simulate", || {
+        guards
+            .iter()
+            .map(|guard| walk(&lab, *guard, args.turn, None, &mut turns, Some(&mut visits)))
+            .collect::<Vec<Outcome>>()
+    });
+    info!("{report}");
+    phases.push(report);
+    for (number, (guard, outcome)) in guards.iter().zip(outcomes).enumerate() {
+        let start = format!("Guard {} from row {} column {} facing {:?}", number + 1, guard.position.0, guard.position.1, guard.direction);
+        match outcome {
+            Outcome::Exits(last) => println!("{start} leaves from row {} column {} facing {:?}", last.position.0, last.position.1, last.direction),
+            Outcome::Loops { steps, turns } => println!("{start} loops every {steps} steps and {turns} turns"),
+        }
     }
 
-    print_map(&map_state.map, map_state.map_string);
-    info!(
-        "Number of positions visited: {}",
-        map_state.guard_states.into_iter().map(|x| x.1).unique().collect::<Vec<(usize,usize)>>().len() + 1
-    );
-    info!("Number of loops is wrong it should be 1309. I can't figure out why not :((((( : {}", map_state.number_of_loops);
+    let rendered = render_map(&lab, &visits, &[], &[]);
+    print!("{rendered}");
+    if let Some(name) = &args.snapshot {
+        if !snapshot::check(name, &rendered, args.update_snapshots) {
+            std::process::exit(1);
+        }
+    }
+    let path: Vec<(usize, usize)> = visits.visited().into_iter().map(|cell| lab.position(cell)).collect();
+    println!("Answer 1: {}", path.len());
+
+    let (obstructions, report) = measure("obstructions", || loop_obstructions(&lab, &path, &guards, args.turn));
+    info!("{report}");
+    phases.push(report);
+    println!("Answer 2: {}", obstructions.len());
+
+    let obstructions: Vec<(usize, usize)> = obstructions.into_iter().sorted().collect();
+    if args.list_obstructions {
+        for (row, col) in obstructions.iter() {
+            println!("Obstruction at row {row} column {col}");
+        }
+        print!("{}", render_map(&lab, &visits, &[], &obstructions));
+    }
+    if args.render_loops {
+        for obstruction in obstructions.iter() {
+            println!("\nObstruction at row {} column {}", obstruction.0, obstruction.1);
+            let mut loop_visits = Visits::new(&lab);
+            for guard in guards.iter() {
+                walk(&lab, *guard, args.turn, Some(*obstruction), &mut turns, Some(&mut loop_visits));
+            }
+            print!("{}", render_map(&lab, &loop_visits, &guards, &[*obstruction]));
+        }
+    }
+
+    if let Some(bench_json) = args.bench_json {
+        write_bench_json(&bench_json, "december_6", &phases);
+    }
 }
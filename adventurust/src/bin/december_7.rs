@@ -1,15 +1,16 @@
-use std::{fs, ptr::eq, vec};
-use clap::Parser;
+use std::{fmt::Debug, fs, sync::Arc};
+use adventurust::inspect::{InputStats, InspectFormat};
+use adventurust::progress::Progress;
+use clap::{Parser, ValueEnum};
 use log::{self, debug, info, trace};
-use itertools::{any, Itertools};
 
 
 /*
 The smart way to do this, I think, is to:
 1. Memorize parts of the equations (?)
 2. Do a binary search of some sort where you sort the potential equations by how much the operator will increase.
-Since left to right implies that order does not matter for which sign you choose. So something like 
-[8,9,1,6] would mean that an order of choice of [0,0,0,0] = ["+","+","*","+"] would be the minimum amd you know that 
+Since left to right implies that order does not matter for which sign you choose. So something like
+[8,9,1,6] would mean that an order of choice of [0,0,0,0] = ["+","+","*","+"] would be the minimum amd you know that
 [3,4,1,2] is the order of increase. So you evaluation [0,0,0,0] if it's too high, fail, if it's too low, then increment half
 like so [0,0,1,1] and if that's too high, then [0,0,0,1]. If that's too high then [0,0,1,0] and if that's too low if fails
 
@@ -24,197 +25,348 @@ struct Equation {
     remaining_values: Vec<i64>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum Operations {
-    Add,
-    Multiply,
-    Concatenate,
-}
-// Consider implemneting look up for minimum value here
-
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
     file_path: String,
     target_number: Option<i64>,
+    /// Print summary statistics of the parsed input instead of solving
+    #[arg(long, value_enum)]
+    inspect: Option<InspectFormat>,
+    /// How to look for a working set of operators
+    #[arg(long, value_enum, default_value_t = Solver::Backward)]
+    solver: Solver,
+    /// Allow another operator in both parts, can be given more than once.
+    /// One of sub, div, pow, xor, or concat:B to concatenate in base B
+    #[arg(long = "operator", value_parser = parse_operator)]
+    operators: Vec<Arc<dyn Operator>>,
 }
 
-fn equation_to_string(equation: &Equation, operations: &Vec<Operations>) -> String {
-    let mut result = equation.first_value.to_string();
-    for (index, operation) in operations.iter().enumerate() {
-        let part = equation.remaining_values[index];
-        if *operation == Operations::Add {
-            result += " + ";
-            
-        } else if *operation == Operations::Multiply {
-            result += " * ";
-        } else {
-            result += " || ";
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Solver {
+    /// Undo operators from the target back to the first value
+    Backward,
+    /// Try every combination of operators
+    Search,
+    /// Build every result up from the first value
+    Recursive,
+}
+
+// The left operands an operator could have had, given its result and right operand
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inverse {
+    Lefts(Vec<i64>),
+    // Any left operand works, like multiplying by zero to get zero, as long as there is one
+    AnyLeft,
+}
+
+// How to undo an operator for the backward solver
+trait Invert {
+    fn lefts(&self, target: i64, right: i64) -> Inverse;
+}
+
+/*
+Operators are applied left to right like the puzzle's + and *. To help the backward solver
+an operator can hand back how to undo itself, one that can't keeps the default of None and
+equations using it are solved forwards instead.
+*/
+trait Operator: Debug + Send + Sync {
+    fn symbol(&self) -> String;
+
+    // None if the result isn't defined or doesn't fit in an i64
+    fn apply(&self, left: i64, right: i64) -> Option<i64>;
+
+    fn inverse(&self) -> Option<&dyn Invert> {
+        None
+    }
+
+    fn invertible(&self) -> bool {
+        self.inverse().is_some()
+    }
+
+    // Never makes a non-negative running value smaller with this right operand, which lets the solvers prune
+    fn grows(&self, _right: i64) -> bool {
+        false
+    }
+}
+
+#[derive(Debug)]
+struct Add;
+
+impl Operator for Add {
+    fn symbol(&self) -> String {
+        "+".to_string()
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        left.checked_add(right)
+    }
+
+    fn inverse(&self) -> Option<&dyn Invert> {
+        Some(self)
+    }
+
+    fn grows(&self, right: i64) -> bool {
+        right >= 0
+    }
+}
+
+impl Invert for Add {
+    fn lefts(&self, target: i64, right: i64) -> Inverse {
+        Inverse::Lefts(target.checked_sub(right).into_iter().collect())
+    }
+}
+
+#[derive(Debug)]
+struct Multiply;
+
+impl Operator for Multiply {
+    fn symbol(&self) -> String {
+        "*".to_string()
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        left.checked_mul(right)
+    }
+
+    fn inverse(&self) -> Option<&dyn Invert> {
+        Some(self)
+    }
+
+    fn grows(&self, right: i64) -> bool {
+        right >= 1
+    }
+}
+
+impl Invert for Multiply {
+    fn lefts(&self, target: i64, right: i64) -> Inverse {
+        match right {
+            0 if target == 0 => Inverse::AnyLeft,
+            0 => Inverse::Lefts(Vec::new()),
+            _ if target % right == 0 => Inverse::Lefts(vec![target / right]),
+            _ => Inverse::Lefts(Vec::new()),
         }
-        result += &part.to_string();
     }
-    result
 }
 
-fn compute_operation(
-    first_value: i64,
-    operation: Operations,
-    second_value: i64,
-) -> i64 {
-    match operation {
-        Operations::Add => first_value + second_value,
-        Operations::Multiply => first_value * second_value,
-        Operations::Concatenate => {
-            let mut new_value = first_value.to_string();
-            new_value += &second_value.to_string();
-            new_value.parse::<i64>().unwrap()
+// The puzzle's || is base 10
+#[derive(Debug)]
+struct Concatenate {
+    base: i64,
+}
+
+impl Concatenate {
+    // What the left operand gets multiplied by to make room for the digits of the right
+    fn shift(&self, right: i64) -> Option<i64> {
+        let digits = if right == 0 { 1 } else { right.checked_ilog(self.base)? + 1 };
+        self.base.checked_pow(digits)
+    }
+}
+
+impl Operator for Concatenate {
+    fn symbol(&self) -> String {
+        if self.base == 10 { "||".to_string() } else { format!("||{}", self.base) }
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        if left < 0 || right < 0 {
+            return None;
         }
+        left.checked_mul(self.shift(right)?)?.checked_add(right)
+    }
+
+    fn inverse(&self) -> Option<&dyn Invert> {
+        Some(self)
+    }
+
+    fn grows(&self, right: i64) -> bool {
+        right >= 0
     }
 }
 
-fn compute_operations(equation: &Equation, operations: &Vec<Operations>) -> i64 {
-    let mut result = equation.first_value;
-    for (index, operation) in operations.iter().enumerate() {
-        let part = equation.remaining_values[index];
-        result = compute_operation(result, *operation, part);
+impl Invert for Concatenate {
+    // The target has to end in the right operand's digits, and what's in front of them is the left
+    fn lefts(&self, target: i64, right: i64) -> Inverse {
+        let lefts = match self.shift(right) {
+            _ if right < 0 || target < right => Vec::new(),
+            Some(shift) if (target - right) % shift == 0 => vec![(target - right) / shift],
+            Some(_) => Vec::new(),
+            // Too many digits to shift anything but zero out of the way
+            None if target == right => vec![0],
+            None => Vec::new(),
+        };
+        Inverse::Lefts(lefts)
+    }
+}
+
+#[derive(Debug)]
+struct Subtract;
+
+impl Operator for Subtract {
+    fn symbol(&self) -> String {
+        "-".to_string()
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        left.checked_sub(right)
+    }
+
+    fn inverse(&self) -> Option<&dyn Invert> {
+        Some(self)
     }
-    result
 }
 
-fn calculate_rank(parts: &Vec<i64>) -> Vec<usize> {
-    let mut indexed_parts: Vec<(usize, &i64)> = parts.iter().enumerate().collect();
-    indexed_parts.sort_by(|a, b| a.1.cmp(b.1));
-    
-    let mut ranks = vec![0; parts.len()];
-    for (rank, (index, _)) in indexed_parts.iter().enumerate() {
-        ranks[*index] = rank;
+impl Invert for Subtract {
+    fn lefts(&self, target: i64, right: i64) -> Inverse {
+        Inverse::Lefts(target.checked_add(right).into_iter().collect())
     }
-    trace!("\nRanks: {ranks:?}\nParts: {parts:?}");
-    ranks
 }
 
-fn number_to_rank_boolean(number: i32, length: i32, ranks: Vec<usize>) -> Vec<bool> {
-    let mut result = vec![false; length as usize];
-    let mut num = number;
-    
-    for i in 0..length {
-        result[i as usize] = num & 1 == 1;
-        num >>= 1;
+// Rounds towards zero, a whole range of left operands divide down to the same result so there's no inverse
+#[derive(Debug)]
+struct Divide;
+
+impl Operator for Divide {
+    fn symbol(&self) -> String {
+        "/".to_string()
     }
-    let mut ordered_result = vec![false; length as usize];
-    for (index, rank) in ranks.iter().enumerate() {
-        ordered_result[*rank] = result[index];
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        left.checked_div(right)
     }
-    ordered_result
 }
 
-fn map_to_operations(bools: &Vec<bool>, parts: Vec<i64>, first_value: i64) -> Vec<Operations> {
-    let mut operations = Vec::new();
-    for (index, part) in parts.iter().enumerate() {
-        if !bools[index] {
-            if (*part == 1 )|| (first_value == 1 && index==0){
-                operations.push(Operations::Multiply);    
-            }else{
-                operations.push(Operations::Add);
-            }
-            
-        } else {
-            if (*part  == 1) || (first_value == 1 && index==0){
-                operations.push(Operations::Add);
-            } else{
-                operations.push(Operations::Multiply);
-            }
+#[derive(Debug)]
+struct Power;
+
+// The non-negative whole number that raised to `exponent` gives `target`, if there is one
+fn integer_root(target: i64, exponent: u32) -> Option<i64> {
+    let guess = (target as f64).powf(1.0 / exponent as f64).round() as i64;
+    (guess.saturating_sub(1)..=guess.saturating_add(1))
+        .find(|root| *root >= 0 && root.checked_pow(exponent) == Some(target))
+}
+
+impl Operator for Power {
+    fn symbol(&self) -> String {
+        "^".to_string()
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        left.checked_pow(u32::try_from(right).ok()?)
+    }
+
+    fn inverse(&self) -> Option<&dyn Invert> {
+        Some(self)
+    }
+}
+
+impl Invert for Power {
+    fn lefts(&self, target: i64, right: i64) -> Inverse {
+        let Ok(exponent) = u32::try_from(right) else {
+            return Inverse::Lefts(Vec::new());
+        };
+        if exponent == 0 {
+            return if target == 1 { Inverse::AnyLeft } else { Inverse::Lefts(Vec::new()) };
         }
+        let lefts = match (target < 0, exponent % 2 == 0) {
+            (false, true) => integer_root(target, exponent).into_iter().flat_map(|root| [root, -root]).collect::<Vec<i64>>(),
+            (false, false) => integer_root(target, exponent).into_iter().collect(),
+            (true, false) => target.checked_neg().and_then(|target| integer_root(target, exponent)).map(|root| -root).into_iter().collect(),
+            (true, true) => Vec::new(),
+        };
+        // An even power of zero would otherwise be listed twice
+        Inverse::Lefts(lefts.into_iter().fold(Vec::new(), |mut lefts, left| {
+            if !lefts.contains(&left) {
+                lefts.push(left);
+            }
+            lefts
+        }))
     }
-    
-    operations
 }
 
+#[derive(Debug)]
+struct Xor;
 
-fn search_for_solution_no_concatenate(equation: Equation) -> bool{
-    // https://stackoverflow.com/a/75693863
-    let num_operations = equation.remaining_values.len();
-    let total_combinations = (2 as i32).pow(num_operations as u32);
-    let mut left = 0;
-    let mut right: i32 = total_combinations as i32-1;
-    
-    let parts_rank = calculate_rank(&equation.remaining_values);
-    // Unfortunately I can't figure out how to order the precedence of the operations in terms of their importants.
-    // I think there's probably a way to do it based on both operands, but unfortunately i ran out of time. Brute forcing it.
-    // while left <= right {
-    //     let mid = left + (right - left) / 2;
-    //     let bools = number_to_rank_boolean(mid, num_operations as i32, parts_rank.clone());
-    //     let operations: Vec<Operations> = map_to_operations(&bools, equation.remaining_values.clone(), equation.first_value);
-    //     let result = compute_operations(&equation, &operations);
-    //     let target = equation.target;
-    //     let equation_string =  equation_to_string(&equation, &operations);
-    //     trace!("Equation: {equation_string}={result} -->  {target}\n\tOperations: {operations:?}\n\t{parts_rank:?}\n\t{bools:?}\n\t total:{total_combinations} left:{left} right:{right} mid:{mid}");
-    //     if  result == equation.target {
-    //         debug!("Found solution: {:?}, {:?}", result, operations);
-    //         return true;
-    //     } else if result > equation.target{
-    //         right = mid - 1;
-    //     } else {
-    //         left = mid + 1;
-    //     }
-    //     if left > right {
-            
-    //         debug!("No solution found: {target} ({result}): {left}>{right} ({total_combinations}). Operations {:?}", operations);
-    //     }
-    // }
-    for i in 0..total_combinations {
-        
-        let bools = number_to_rank_boolean(i, num_operations as i32, parts_rank.clone());
-        let operations: Vec<Operations> = map_to_operations(&bools, equation.remaining_values.clone(), equation.first_value);
-        let result = compute_operations(&equation, &operations);
-        let target = equation.target;
-        let equation_string =  equation_to_string(&equation, &operations);
-        trace!("Equation: {equation_string}={result} -->  {target}\n\tOperations: {operations:?}\n\t{parts_rank:?}\n\t{bools:?}\n\t total:{total_combinations}");
-        if  result == equation.target {
-            debug!("Found solution: {:?}, {:?}", result, operations);
-            return true;
-        }
+impl Operator for Xor {
+    fn symbol(&self) -> String {
+        "^^".to_string()
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        Some(left ^ right)
     }
-    false
-}   
 
+    fn inverse(&self) -> Option<&dyn Invert> {
+        Some(self)
+    }
+}
 
-fn generate_operation_combinations(length: usize) -> Vec<Vec<Operations>> {
-    let total_combinations = 3_usize.pow(length as u32);
-    let mut result = Vec::with_capacity(total_combinations);
-    
-    for i in 0..total_combinations {
-        let mut combination = Vec::with_capacity(length);
-        let mut num = i;
-        
-        for _ in 0..length {
-            let operation = match num % 3 {
-                0 => Operations::Add,
-                1 => Operations::Multiply,
-                2 => Operations::Concatenate,
-                _ => unreachable!()
-            };
-            combination.push(operation);
-            num /= 3;
+impl Invert for Xor {
+    fn lefts(&self, target: i64, right: i64) -> Inverse {
+        Inverse::Lefts(vec![target ^ right])
+    }
+}
+
+fn parse_operator(name: &str) -> Result<Arc<dyn Operator>, String> {
+    if let Some(base) = name.strip_prefix("concat:") {
+        let base: i64 = base.parse().map_err(|_| format!("Bad base in {name}"))?;
+        if base < 2 {
+            return Err(format!("Can't concatenate in base {base}"));
         }
-        
-        result.push(combination);
+        return Ok(Arc::new(Concatenate { base }));
+    }
+    match name {
+        "add" => Ok(Arc::new(Add)),
+        "mul" => Ok(Arc::new(Multiply)),
+        "concat" => Ok(Arc::new(Concatenate { base: 10 })),
+        "sub" => Ok(Arc::new(Subtract)),
+        "div" => Ok(Arc::new(Divide)),
+        "pow" => Ok(Arc::new(Power)),
+        "xor" => Ok(Arc::new(Xor)),
+        _ => Err(format!("Unknown operator {name}, expected add, mul, concat, concat:B, sub, div, pow or xor")),
+    }
+}
+
+fn equation_to_string(equation: &Equation, operators: &[Arc<dyn Operator>], combination: &[usize]) -> String {
+    let mut result = equation.first_value.to_string();
+    for (index, operator) in combination.iter().enumerate() {
+        result += &format!(" {} {}", operators[*operator].symbol(), equation.remaining_values[index]);
     }
-    
     result
 }
 
-fn search_for_solution_with_concatenate(equation: Equation) -> bool{
+fn compute_operations(equation: &Equation, operators: &[Arc<dyn Operator>], combination: &[usize]) -> Option<i64> {
+    let mut result = equation.first_value;
+    for (index, operator) in combination.iter().enumerate() {
+        result = operators[*operator].apply(result, equation.remaining_values[index])?;
+    }
+    Some(result)
+}
+
+// The i-th combination of operators, counting in base (number of operators)
+fn operation_combination(i: u64, length: usize, num_operators: usize) -> Vec<usize> {
+    let mut combination = Vec::with_capacity(length);
+    let mut num = i;
+    for _ in 0..length {
+        combination.push((num % num_operators as u64) as usize);
+        num /= num_operators as u64;
+    }
+    combination
+}
+
+fn search_for_solution(equation: &Equation, operators: &[Arc<dyn Operator>]) -> bool {
     let num_operations = equation.remaining_values.len();
+    let total_combinations = (operators.len() as u64)
+        .checked_pow(num_operations as u32)
+        .expect("Too many combinations of operators to search");
 
-    
-    for operations in generate_operation_combinations(num_operations) {
-        let result = compute_operations(&equation, &operations);
+    for i in 0..total_combinations {
+        let combination = operation_combination(i, num_operations, operators.len());
+        let result = compute_operations(equation, operators, &combination);
         let target = equation.target;
-        let equation_string =  equation_to_string(&equation, &operations);
-        trace!("Equation: {equation_string}={result} -->  {target}\n\tOperations: {operations:?}");
-        if  result == equation.target {
-            debug!("Found solution: {:?}: {}", result, equation_to_string(&equation, &operations));
+        trace!("Equation: {}={result:?} -->  {target}", equation_to_string(equation, operators, &combination));
+        if result == Some(equation.target) {
+            debug!("Found solution: {}", equation_to_string(equation, operators, &combination));
             return true;
         }
     }
@@ -242,31 +394,77 @@ fn get_equations(file_path: String) -> Vec<Equation>{
 }
 
 
-fn recursive_answer(equation: &Equation, acc: i64, operations: &Vec<Operations>) -> bool{
-    if equation.target < acc {
-       return false
-    }
-    if equation.remaining_values.len() == 0 {
-        return acc == equation.target;
+fn inspect_equations(equations: &[Equation], num_operators: usize) -> InputStats {
+    let mut stats = InputStats::new();
+    stats
+        .count("equations", equations.len())
+        .extrema("operands", equations.iter().map(|equation| equation.remaining_values.len() as i64 + 1))
+        .extrema("target", equations.iter().map(|equation| equation.target))
+        .extrema("operand value", equations.iter().flat_map(|equation| {
+            std::iter::once(equation.first_value).chain(equation.remaining_values.iter().copied())
+        }))
+        .count("part 2 operators", num_operators)
+        .integer("largest part 2 search space", equations.iter()
+            .map(|equation| (num_operators as i64).saturating_pow(equation.remaining_values.len() as u32))
+            .max()
+            .unwrap_or(0))
+        .histogram("operands per equation", equations.iter().map(|equation| equation.remaining_values.len() + 1));
+    stats
+}
+
+
+// When every step grows the running value, going past the target means it can't come back down
+fn solve_forwards(target: i64, acc: i64, values: &[i64], operators: &[Arc<dyn Operator>], grows: bool) -> bool {
+    if grows && acc > target {
+        return false;
     }
-    let mut bools = Vec::new();
-    let new_first_value = equation.remaining_values[0];
-    let new_remaining_values = equation.remaining_values[1..].to_vec();
-    let new_equation = Equation {
-        target: equation.target,
-        first_value: new_first_value,
-        remaining_values: new_remaining_values
+    let Some((next, rest)) = values.split_first() else {
+        return acc == target;
     };
-    for operation in operations.iter() {
-        debug!("Trying operation: {operation:?} -- {acc} -- {new_first_value}");
-        bools.push(recursive_answer(&new_equation, compute_operation(acc, *operation, new_first_value), operations));
-    }
-    
-    return any(bools, |b| b) 
-    
+    operators.iter().any(|operator| {
+        trace!("Trying operation: {} -- {acc} -- {next}", operator.symbol());
+        operator.apply(acc, *next).is_some_and(|value| solve_forwards(target, value, rest, operators, grows))
+    })
+}
+
+// Whether some choice of operators gets through the values without overflowing or dividing by zero
+fn evaluates(acc: i64, values: &[i64], operators: &[Arc<dyn Operator>]) -> bool {
+    let Some((next, rest)) = values.split_first() else {
+        return true;
+    };
+    operators.iter().any(|operator| operator.apply(acc, *next).is_some_and(|value| evaluates(value, rest, operators)))
 }
 
+/*
+Works from the target back towards the first value, undoing the last operator each time:
+for the puzzle's operators an add only if the target is at least the last value, a multiply
+only if it divides evenly and a concatenate only if the target ends in the last value's
+digits. Most branches fail one of those straight away, and `any` stops at the first branch
+that works. When every step grows the running value nothing before the last operator can
+be below the first value, so those branches are dropped too.
+*/
+fn solve_backwards(target: i64, first_value: i64, values: &[i64], operators: &[Arc<dyn Operator>], grows: bool) -> bool {
+    let Some((last, rest)) = values.split_last() else {
+        return target == first_value;
+    };
+    operators.iter().any(|operator| match operator.inverse().expect("Operator can't be undone").lefts(target, *last) {
+        // Whatever the rest of the equation comes to this operator turns it into the target, but it still has to come to something
+        Inverse::AnyLeft => evaluates(first_value, rest, operators),
+        Inverse::Lefts(lefts) => lefts
+            .into_iter()
+            .any(|left| !(grows && left < first_value) && solve_backwards(left, first_value, rest, operators, grows)),
+    })
+}
 
+fn solvable(equation: &Equation, operators: &[Arc<dyn Operator>], solver: Solver) -> bool {
+    let grows = equation.first_value >= 0
+        && operators.iter().all(|operator| equation.remaining_values.iter().all(|value| operator.grows(*value)));
+    match solver {
+        Solver::Backward => solve_backwards(equation.target, equation.first_value, &equation.remaining_values, operators, grows),
+        Solver::Search => search_for_solution(equation, operators),
+        Solver::Recursive => solve_forwards(equation.target, equation.first_value, &equation.remaining_values, operators, grows),
+    }
+}
 
 fn main(){
     env_logger::init();
@@ -278,34 +476,28 @@ fn main(){
     if args.target_number.is_some() {
         equations.retain(|equation| equation.target == args.target_number.unwrap());
     }
-    for equation in equations.iter() {
-        if search_for_solution_no_concatenate(equation.clone()){
-            sum += equation.target;
-        }
-    }
-    info!("Answer 1: {sum}");
-    let mut concact_sum = 0;
-    for equation in equations.iter() {
-        if search_for_solution_with_concatenate(equation.clone()){
-            concact_sum += equation.target;
-        }
+    let part_1: Vec<Arc<dyn Operator>> = vec![Arc::new(Add), Arc::new(Multiply)];
+    let part_2: Vec<Arc<dyn Operator>> = vec![Arc::new(Add), Arc::new(Multiply), Arc::new(Concatenate { base: 10 })];
+    if let Some(format) = args.inspect {
+        inspect_equations(&equations, part_2.len() + args.operators.len()).print(format);
+        return;
     }
-    
-    info!("Answer 2: {concact_sum}");
-    
-    let mut recursive_sum = 0;
-    for equation in equations.iter() {
-        if recursive_answer(equation, equation.first_value, &vec![Operations::Add, Operations::Multiply]) {
-            recursive_sum += equation.target;
+    for (number, operators) in [part_1, part_2].into_iter().enumerate() {
+        let operators: Vec<Arc<dyn Operator>> = operators.into_iter().chain(args.operators.iter().cloned()).collect();
+        let mut solver = args.solver;
+        if solver == Solver::Backward && !operators.iter().all(|operator| operator.invertible()) {
+            info!("Not every operator can be undone, solving part {} forwards instead", number + 1);
+            solver = Solver::Recursive;
         }
-    }
-    info!("Answer 1 -- recursive: {recursive_sum}");
-    
-    recursive_sum = 0;
-    for equation in equations.iter() {
-        if recursive_answer(equation, equation.first_value, &vec![Operations::Add, Operations::Multiply, Operations::Concatenate]) {
-            recursive_sum += equation.target;
+        let mut progress = Progress::new(&format!("Part {} equations", number + 1), Some(equations.len() as u64));
+        for equation in equations.iter() {
+            if solvable(equation, &operators, solver) {
+                sum += equation.target;
+            }
+            progress.inc(1);
         }
+        progress.finish();
+        println!("Answer {}: {sum}", number + 1);
+        sum = 0;
     }
-    info!("Answer 2 -- recursive: {recursive_sum}");
-}
\ No newline at end of file
+}
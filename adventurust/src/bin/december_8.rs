@@ -1,4 +1,6 @@
 use std::{collections::HashSet, fs, hash::Hash};
+use adventurust::inspect::{InputStats, InspectFormat};
+use adventurust::snapshot;
 use clap::Parser;
 use log::{self, debug, info};
 use itertools::Itertools;
@@ -46,21 +48,27 @@ impl Map {
         self.antennae.iter().map(|antenna| antenna.frequency).unique().collect()
     }
 
-    fn draw_map(&self) {
+    fn render_map(&self) -> String {
+        let mut rendered = String::new();
         for y in 0..self.height {
             for x in 0..self.width {
-                let antenna: Vec<Antenna> = self.antennae.clone().into_iter().filter(|antenna| antenna.location.x == x && antenna.location.y == y).collect();
+                let antenna: Vec<&Antenna> = self.antennae.iter().filter(|antenna| antenna.location.x == x && antenna.location.y == y).collect();
                 if antenna.len() > 0 {
-                    print!("{}", antenna[0].frequency);
+                    rendered.push(antenna[0].frequency);
                 } else if self.antinode_locations.contains(&Coordinate { x, y }) {
-                    print!("#");
+                    rendered.push('#');
                 } else {
-                    print!(".");
+                    rendered.push('.');
                 }
+            }
+            rendered.push('\n');
         }
-        println!();
+        rendered
+    }
+
+    fn draw_map(&self) {
+        print!("{}", self.render_map());
     }
-}
 
 }
 
@@ -95,6 +103,15 @@ fn get_antinodes(antenna_1: &Antenna, antenna_2: &Antenna, map: &Map) -> Vec<Coo
 struct Cli {
     file_path: String,
     target_number: Option<i64>,
+    /// Compare both antinode maps against the stored snapshots with this name
+    #[arg(long)]
+    snapshot: Option<String>,
+    /// Overwrite the stored snapshots instead of comparing against them
+    #[arg(long)]
+    update_snapshots: bool,
+    /// Print summary statistics of the parsed input instead of solving
+    #[arg(long, value_enum)]
+    inspect: Option<InspectFormat>,
 }
 
 fn get_map(file_path: String) -> Map {
@@ -124,6 +141,22 @@ fn get_map(file_path: String) -> Map {
     }
 }
 
+fn inspect_map(map: &Map) -> InputStats {
+    let frequencies = map.get_all_frequencies();
+    let mut stats = InputStats::new();
+    stats
+        .integer("width", map.width as i64)
+        .integer("height", map.height as i64)
+        .count("antennae", map.antennae.len())
+        .count("distinct frequencies", frequencies.len())
+        .count("antenna pairs", frequencies.iter()
+            .map(|frequency| map.get_antenna_of_same_frequency(*frequency).len())
+            .map(|n| n * n.saturating_sub(1) / 2)
+            .sum())
+        .histogram("antennae per frequency", map.antennae.iter().map(|antenna| antenna.frequency));
+    stats
+}
+
 /*
 Part 2 is intractable to do all pairwise combos. 
 
@@ -194,8 +227,12 @@ fn main(){
     env_logger::init();
 
     let args = Cli::parse();
-    let file_path = args.file_path;
+    let file_path = args.file_path.clone();
     let mut map = get_map(file_path);
+    if let Some(format) = args.inspect {
+        inspect_map(&map).print(format);
+        return;
+    }
     
     let frequencies = map.get_all_frequencies();
     let mut antinodes = HashSet::new();
@@ -215,6 +252,10 @@ fn main(){
     debug!("Map: {map:?}");
     info!("Answer 1: {}", map.antinode_locations.len());
     map.draw_map();
+    let mut snapshots_match = true;
+    if let Some(name) = &args.snapshot {
+        snapshots_match &= snapshot::check(&format!("{name}_part_1"), &map.render_map(), args.update_snapshots);
+    }
 
     map.antinode_locations = HashSet::new();
     info!("Part 2");
@@ -234,4 +275,10 @@ fn main(){
     map.antinode_locations = antinodes_all_distance;
     info!("Answer 2: {}", map.antinode_locations.len());
     map.draw_map();
+    if let Some(name) = &args.snapshot {
+        snapshots_match &= snapshot::check(&format!("{name}_part_2"), &map.render_map(), args.update_snapshots);
+    }
+    if !snapshots_match {
+        std::process::exit(1);
+    }
 }
\ No newline at end of file
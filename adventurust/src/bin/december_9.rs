@@ -1,4 +1,6 @@
 use std::{collections::HashMap, fs};
+use adventurust::inspect::{InputStats, InspectFormat};
+use adventurust::snapshot;
 use clap::Parser;
 use log::{self, debug, info};
 use itertools::enumerate;
@@ -24,6 +26,15 @@ const MAX_SIZE: i32 = 9;
 struct Cli {
     file_path: String,
     target_number: Option<i64>,
+    /// Compare both compacted layouts against the stored snapshots with this name
+    #[arg(long)]
+    snapshot: Option<String>,
+    /// Overwrite the stored snapshots instead of comparing against them
+    #[arg(long)]
+    update_snapshots: bool,
+    /// Print summary statistics of the parsed input instead of solving
+    #[arg(long, value_enum)]
+    inspect: Option<InspectFormat>,
 }
 
 fn get_file_map(file_path: String) -> String {
@@ -219,24 +230,50 @@ fn checksum(file_layout: &Vec<Option<u32>>) -> i64 {
     checksum
 }
 
-fn print_file_layout(file_layout: &Vec<Option<u32>>) {
+fn render_file_layout(file_layout: &Vec<Option<u32>>) -> String {
+    let mut rendered = String::new();
     for file in file_layout {
         if file.is_some() {
-            print!("{}", file.unwrap());
+            rendered += &file.unwrap().to_string();
         } else {
-            print!(".");
+            rendered.push('.');
         }
     }
-    println!();
+    rendered.push('\n');
+    rendered
+}
+
+fn print_file_layout(file_layout: &Vec<Option<u32>>) {
+    print!("{}", render_file_layout(file_layout));
+}
+
+fn inspect_file_map(file_map: &str) -> InputStats {
+    let digits: Vec<u32> = file_map.trim().chars().map(|c| c.to_digit(10).unwrap()).collect();
+    let file_sizes: Vec<u32> = digits.iter().step_by(2).copied().collect();
+    let free_sizes: Vec<u32> = digits.iter().skip(1).step_by(2).copied().collect();
+    let mut stats = InputStats::new();
+    stats
+        .count("disk map length", digits.len())
+        .count("files", file_sizes.len())
+        .count("total blocks", digits.iter().sum::<u32>() as usize)
+        .count("file blocks", file_sizes.iter().sum::<u32>() as usize)
+        .count("free blocks", free_sizes.iter().sum::<u32>() as usize)
+        .histogram("file size", file_sizes)
+        .histogram("free span size", free_sizes);
+    stats
 }
 
 fn main(){
     env_logger::init();
 
     let args = Cli::parse();
-    let file_path = args.file_path;
+    let file_path = args.file_path.clone();
     
     let file_map = get_file_map(file_path);
+    if let Some(format) = args.inspect {
+        inspect_file_map(&file_map).print(format);
+        return;
+    }
     let size = vector_size(&file_map);
     let vector = allocate_vector(&file_map, size);
     let mut naive_reallocate = vector.clone();
@@ -247,5 +284,12 @@ fn main(){
     let mut defrag_reallocate = vector.clone();
     defrag_files(&mut defrag_reallocate);
     info!("Answer 2: {}", checksum(&defrag_reallocate));
-    
+
+    if let Some(name) = &args.snapshot {
+        let part_1 = snapshot::check(&format!("{name}_part_1"), &render_file_layout(&naive_reallocate), args.update_snapshots);
+        let part_2 = snapshot::check(&format!("{name}_part_2"), &render_file_layout(&defrag_reallocate), args.update_snapshots);
+        if !(part_1 && part_2) {
+            std::process::exit(1);
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+use clap::ValueEnum;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/*
+Summary statistics for a parsed puzzle input, so we can size up a file before
+picking an algorithm. Each day fills one of these in from its own parse step and
+prints it with `--inspect table` or `--inspect json`.
+*/
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InspectFormat {
+    Table,
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub enum StatValue {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Histogram(Vec<(String, usize)>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InputStats {
+    entries: Vec<(String, StatValue)>,
+}
+
+impl Serialize for StatValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StatValue::Integer(value) => serializer.serialize_i64(*value),
+            StatValue::Float(value) => serializer.serialize_f64(*value),
+            StatValue::Text(value) => serializer.serialize_str(value),
+            StatValue::Histogram(buckets) => {
+                let mut map = serializer.serialize_map(Some(buckets.len()))?;
+                for (bucket, count) in buckets {
+                    map.serialize_entry(bucket, count)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+// Keep the entries in the order they were added rather than sorting the keys
+impl Serialize for InputStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.entries.iter().map(|(name, value)| (name, value)))
+    }
+}
+
+impl InputStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&mut self, name: &str, value: usize) -> &mut Self {
+        self.entries.push((name.to_string(), StatValue::Integer(value as i64)));
+        self
+    }
+
+    pub fn integer(&mut self, name: &str, value: i64) -> &mut Self {
+        self.entries.push((name.to_string(), StatValue::Integer(value)));
+        self
+    }
+
+    pub fn text(&mut self, name: &str, value: impl ToString) -> &mut Self {
+        self.entries.push((name.to_string(), StatValue::Text(value.to_string())));
+        self
+    }
+
+    // Adds `<name> min`, `<name> max` and `<name> mean`, skipped entirely when there are no values
+    pub fn extrema(&mut self, name: &str, values: impl IntoIterator<Item = i64>) -> &mut Self {
+        let values: Vec<i64> = values.into_iter().collect();
+        if values.is_empty() {
+            return self;
+        }
+        let mean = values.iter().map(|x| *x as f64).sum::<f64>() / values.len() as f64;
+        self.integer(&format!("{name} min"), *values.iter().min().unwrap());
+        self.integer(&format!("{name} max"), *values.iter().max().unwrap());
+        self.entries.push((format!("{name} mean"), StatValue::Float(mean)));
+        self
+    }
+
+    // Counts each distinct key, buckets are listed in sorted key order
+    pub fn histogram<K: Ord + ToString>(&mut self, name: &str, keys: impl IntoIterator<Item = K>) -> &mut Self {
+        let mut counts = std::collections::BTreeMap::<K, usize>::new();
+        for key in keys {
+            *counts.entry(key).or_insert(0) += 1;
+        }
+        let buckets = counts.into_iter().map(|(key, count)| (key.to_string(), count)).collect();
+        self.entries.push((name.to_string(), StatValue::Histogram(buckets)));
+        self
+    }
+
+    pub fn to_table(&self) -> String {
+        let width = self.entries.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        let mut table = String::new();
+        for (name, value) in &self.entries {
+            match value {
+                StatValue::Integer(value) => table += &format!("{name:<width$}  {value}\n"),
+                StatValue::Float(value) => table += &format!("{name:<width$}  {value:.2}\n"),
+                StatValue::Text(value) => table += &format!("{name:<width$}  {value}\n"),
+                StatValue::Histogram(buckets) => {
+                    table += &format!("{name}\n");
+                    let bucket_width = buckets.iter().map(|(bucket, _)| bucket.len()).max().unwrap_or(0);
+                    let largest = buckets.iter().map(|(_, count)| *count).max().unwrap_or(1);
+                    for (bucket, count) in buckets {
+                        let bar = "#".repeat((count * 40).div_ceil(largest));
+                        table += &format!("  {bucket:>bucket_width$}  {count:>6}  {bar}\n");
+                    }
+                }
+            }
+        }
+        table
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize input stats")
+    }
+
+    pub fn print(&self, format: InspectFormat) {
+        match format {
+            InspectFormat::Table => print!("{}", self.to_table()),
+            InspectFormat::Json => println!("{}", self.to_json()),
+        }
+    }
+}
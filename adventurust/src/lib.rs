@@ -0,0 +1,11 @@
+pub mod alloc;
+pub mod inspect;
+pub mod progress;
+pub mod prompt;
+pub mod runner;
+pub mod snapshot;
+
+// Only swap out the system allocator when asked to, the bookkeeping isn't free
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static GLOBAL: alloc::CountingAllocator = alloc::CountingAllocator;
@@ -1,4 +1,82 @@
-// TODO: Figure out hwo to link nest commands
+use std::{env, fs, path::PathBuf, process::Command};
+use adventurust::prompt::{extract_examples, prompts_dir, read_prompt, render_prompt};
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Show a day's puzzle statement from the local prompts archive
+    Prompt {
+        day: u32,
+        /// Only print this example block
+        #[arg(long)]
+        example: Option<usize>,
+        /// Write the selected example block to this file
+        #[arg(long, requires = "example")]
+        output: Option<PathBuf>,
+        /// Run the day's solver on the selected example block
+        #[arg(long, requires = "example")]
+        solve: bool,
+        /// Directory holding the december_{x}/prompt.txt files
+        #[arg(long)]
+        prompts_dir: Option<PathBuf>,
+        /// Extra arguments passed to the solver after the input file
+        #[arg(last = true)]
+        solver_args: Vec<String>,
+    },
+}
+
+// The day binaries are built next to this one
+fn solver_path(day: u32) -> PathBuf {
+    let current = env::current_exe().expect("Failed to find current executable");
+    current.parent().unwrap().join(format!("december_{day}{}", env::consts::EXE_SUFFIX))
+}
+
+fn show_prompt(day: u32, example: Option<usize>, output: Option<PathBuf>, solve: bool, prompts_dir: PathBuf, solver_args: Vec<String>) {
+    let prompt = read_prompt(&prompts_dir, day);
+    let examples = extract_examples(&prompt);
+
+    let Some(number) = example else {
+        print!("{}", render_prompt(&prompt, &examples));
+        println!("\nFound {} example blocks", examples.len());
+        return;
+    };
+
+    let Some(example) = examples.iter().find(|example| example.number == number) else {
+        eprintln!("December {day} only has {} example blocks", examples.len());
+        std::process::exit(1);
+    };
+
+    if output.is_none() && !solve {
+        print!("{}", example.text());
+    }
+    if let Some(output) = &output {
+        fs::write(output, example.text()).expect("Failed to write example");
+        eprintln!("Wrote example {number} to {}", output.display());
+    }
+    if solve {
+        let input = output.unwrap_or_else(|| env::temp_dir().join(format!("december_{day}_example_{number}.txt")));
+        fs::write(&input, example.text()).expect("Failed to write example");
+        let status = Command::new(solver_path(day))
+            .arg(&input)
+            .args(solver_args)
+            .status()
+            .unwrap_or_else(|_| panic!("Failed to run december_{day}, build it with `cargo build --bin december_{day}`"));
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
 fn main() {
-    println!("Hello, world!");
+    let args = Cli::parse();
+    match args.command {
+        Commands::Prompt { day, example, output, solve, prompts_dir: dir, solver_args } => {
+            show_prompt(day, example, output, solve, dir.unwrap_or_else(prompts_dir), solver_args);
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+use log::{self, info};
+
+/*
+Progress for long searches. On a terminal it redraws a single line on stderr, when
+piped to a file it logs a line every few seconds instead so the output stays readable.
+The total is optional, without one we can still show a count and rate but no ETA.
+*/
+
+const TTY_INTERVAL: Duration = Duration::from_millis(100);
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct Progress {
+    label: String,
+    total: Option<u64>,
+    done: u64,
+    started: Instant,
+    last_report: Instant,
+    is_tty: bool,
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}h{:02}m", seconds / 3600, (seconds % 3600) / 60)
+    } else if seconds >= 60 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+impl Progress {
+    pub fn new(label: &str, total: Option<u64>) -> Self {
+        let now = Instant::now();
+        Progress {
+            label: label.to_string(),
+            total,
+            done: 0,
+            started: now,
+            last_report: now,
+            is_tty: io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn inc(&mut self, amount: u64) {
+        self.done += amount;
+        self.maybe_report();
+    }
+
+    pub fn set(&mut self, done: u64) {
+        self.done = done;
+        self.maybe_report();
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.done as f64 / self.started.elapsed().as_secs_f64().max(1e-9)
+    }
+
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total?;
+        let rate = self.rate();
+        if self.done == 0 || rate == 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(total.saturating_sub(self.done) as f64 / rate))
+    }
+
+    pub fn status(&self) -> String {
+        let mut status = match self.total {
+            Some(total) => format!(
+                "{}: {}/{} ({:.1}%)",
+                self.label,
+                self.done,
+                total,
+                100.0 * self.done as f64 / total.max(1) as f64
+            ),
+            None => format!("{}: {}", self.label, self.done),
+        };
+        status += &format!(" {:.0}/s", self.rate());
+        if let Some(eta) = self.eta() {
+            status += &format!(" ETA {}", format_duration(eta));
+        }
+        status
+    }
+
+    fn interval(&self) -> Duration {
+        if self.is_tty { TTY_INTERVAL } else { LOG_INTERVAL }
+    }
+
+    fn maybe_report(&mut self) {
+        if self.last_report.elapsed() < self.interval() {
+            return;
+        }
+        self.last_report = Instant::now();
+        self.report();
+    }
+
+    fn report(&self) {
+        if self.is_tty {
+            eprint!("\r\x1b[2K{}", self.status());
+            io::stderr().flush().ok();
+        } else {
+            info!("{}", self.status());
+        }
+    }
+
+    // Quick runs never reported anything, so there's nothing to finish
+    pub fn finish(&mut self) {
+        if self.started.elapsed() < self.interval() {
+            return;
+        }
+        if self.is_tty {
+            eprintln!("\r\x1b[2K{} done in {}", self.status(), format_duration(self.started.elapsed()));
+        } else {
+            info!("{} done in {}", self.status(), format_duration(self.started.elapsed()));
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::{fs, path::{Path, PathBuf}};
+
+/*
+The prompt.txt files are copied straight out of the browser, so the code blocks lost
+their formatting. An example block is recovered as a run of consecutive lines that
+don't read like prose: puzzle inputs are grids, numbers and symbols, while the
+statement is made of sentences.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub number: usize,
+    pub first_line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Example {
+    pub fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        text
+    }
+}
+
+pub fn prompts_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("prompts")
+}
+
+pub fn read_prompt(prompts_dir: &Path, day: u32) -> String {
+    let path = prompts_dir.join(format!("december_{day}")).join("prompt.txt");
+    fs::read_to_string(&path).unwrap_or_else(|_| panic!("No prompt found at {}", path.display()))
+}
+
+// Letters with an apostrophe or hyphen inside, and maybe punctuation around them
+fn is_word(token: &str) -> bool {
+    let inner = token
+        .trim_start_matches(['(', '"', '\''])
+        .trim_end_matches([',', '.', ';', ':', '!', '?', ')', '"', '\'']);
+    let letters = inner.chars().filter(|c| c.is_alphabetic()).count();
+    letters >= 2 && inner.chars().all(|c| c.is_alphabetic() || c == '\'' || c == '-')
+}
+
+fn is_prose(line: &str) -> bool {
+    let trimmed = line.trim();
+    if trimmed.starts_with("---") || trimmed.ends_with(':') {
+        return true;
+    }
+    // Short sentences like "Whoops!"
+    let first_is_word = trimmed.split_whitespace().next().is_some_and(is_word);
+    if first_is_word && trimmed.ends_with(['.', '!', '?']) {
+        return true;
+    }
+    trimmed.split_whitespace().filter(|token| is_word(token)).count() >= 3
+}
+
+/*
+A blank line only ends a block if prose comes next. Inputs with several sections, like the
+rules and updates of day 5 or the claw machines of day 13, keep their blank lines so the
+block can go straight into the solver.
+*/
+pub fn extract_examples(prompt: &str) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut current: Option<Example> = None;
+    let mut blank_lines = 0;
+    for (index, line) in prompt.lines().enumerate() {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+        } else if !is_prose(line) {
+            let example = current.get_or_insert_with(|| Example {
+                number: examples.len() + 1,
+                first_line: index,
+                lines: Vec::new(),
+            });
+            if !example.lines.is_empty() {
+                example.lines.extend((0..blank_lines).map(|_| String::new()));
+            }
+            example.lines.push(line.trim_end().to_string());
+            blank_lines = 0;
+        } else {
+            examples.extend(current.take());
+            blank_lines = 0;
+        }
+    }
+    examples.extend(current);
+    examples
+}
+
+// The statement with each example block boxed in and numbered
+pub fn render_prompt(prompt: &str, examples: &[Example]) -> String {
+    let mut rendered = String::new();
+    let mut examples = examples.iter().peekable();
+    let lines: Vec<&str> = prompt.lines().collect();
+    let mut index = 0;
+    while index < lines.len() {
+        match examples.peek() {
+            Some(example) if example.first_line == index => {
+                rendered += &format!("┌── example {} ({} lines)\n", example.number, example.lines.len());
+                for line in &example.lines {
+                    rendered += &format!("│ {line}\n");
+                }
+                rendered += "└──\n";
+                index += example.lines.len();
+                examples.next();
+            }
+            _ => {
+                rendered += lines[index].trim_end();
+                rendered.push('\n');
+                index += 1;
+            }
+        }
+    }
+    rendered
+}
@@ -0,0 +1,68 @@
+use std::{fmt, fs, time::Instant};
+use serde::Serialize;
+
+use crate::alloc;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseReport {
+    pub name: String,
+    pub elapsed_ms: f64,
+    pub peak_heap_bytes: Option<usize>,
+    pub allocations: Option<usize>,
+    pub bytes_allocated: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchReport<'a> {
+    day: &'a str,
+    alloc_stats: bool,
+    phases: &'a [PhaseReport],
+}
+
+fn human_bytes(bytes: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", units[unit])
+}
+
+impl fmt::Display for PhaseReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {:.2}ms", self.name, self.elapsed_ms)?;
+        if let (Some(peak), Some(allocations), Some(bytes)) = (self.peak_heap_bytes, self.allocations, self.bytes_allocated) {
+            write!(f, " peak heap {} | {} allocations | {} allocated", human_bytes(peak), allocations, human_bytes(bytes))?;
+        }
+        Ok(())
+    }
+}
+
+// Run one phase of a solution, timing it and (with `alloc-stats`) tracking the heap
+pub fn measure<T>(name: &str, phase: impl FnOnce() -> T) -> (T, PhaseReport) {
+    alloc::reset_peak();
+    let before_stats = alloc::stats();
+    let before = Instant::now();
+
+    let result = phase();
+
+    let elapsed = before.elapsed();
+    let after_stats = alloc::stats();
+    let enabled = alloc::enabled();
+    let report = PhaseReport {
+        name: name.to_string(),
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        peak_heap_bytes: enabled.then_some(after_stats.peak_bytes),
+        allocations: enabled.then_some(after_stats.allocations - before_stats.allocations),
+        bytes_allocated: enabled.then_some(after_stats.bytes_allocated - before_stats.bytes_allocated),
+    };
+    (result, report)
+}
+
+pub fn write_bench_json(file_path: &str, day: &str, phases: &[PhaseReport]) {
+    let report = BenchReport { day, alloc_stats: alloc::enabled(), phases };
+    let json = serde_json::to_string_pretty(&report).expect("Failed to serialize bench report");
+    fs::write(file_path, json).expect("Failed to write bench report");
+}
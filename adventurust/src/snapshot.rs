@@ -0,0 +1,78 @@
+use std::{fs, path::PathBuf};
+use log::{self, error, info};
+
+/*
+Snapshots are just the rendered text stored under `tests/snapshots/<name>.snap`.
+Grids keep their shape between runs, so a line by line comparison with a caret
+under each changed column is enough to see where a simulation went wrong.
+*/
+
+pub fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.snap"))
+}
+
+fn column_markers(expected: &str, actual: &str) -> String {
+    let expected_chars: Vec<char> = expected.chars().collect();
+    let actual_chars: Vec<char> = actual.chars().collect();
+    let width = expected_chars.len().max(actual_chars.len());
+    let mut markers = String::new();
+    for i in 0..width {
+        if expected_chars.get(i) == actual_chars.get(i) {
+            markers.push(' ');
+        } else {
+            markers.push('^');
+        }
+    }
+    markers.trim_end().to_string()
+}
+
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let mut result = String::new();
+    for i in 0..expected_lines.len().max(actual_lines.len()) {
+        let expected_line = expected_lines.get(i).copied();
+        let actual_line = actual_lines.get(i).copied();
+        if expected_line == actual_line {
+            continue;
+        }
+        result += &format!("line {}:\n", i + 1);
+        if let Some(line) = expected_line {
+            result += &format!("  - {line}\n");
+        }
+        if let Some(line) = actual_line {
+            result += &format!("  + {line}\n");
+        }
+        if let (Some(expected_line), Some(actual_line)) = (expected_line, actual_line) {
+            result += &format!("    {}\n", column_markers(expected_line, actual_line));
+        }
+    }
+    result
+}
+
+// Compare a rendering against its stored snapshot, writing it instead when updating
+pub fn check(name: &str, rendered: &str, update: bool) -> bool {
+    let path = snapshot_path(name);
+    if update {
+        fs::create_dir_all(path.parent().unwrap()).expect("Failed to create snapshot directory");
+        fs::write(&path, rendered).expect("Failed to write snapshot");
+        info!("Wrote snapshot {}", path.display());
+        return true;
+    }
+
+    // A missing snapshot fails so tests can't quietly create new ones
+    if !path.exists() {
+        error!("snapshot {name} missing, rerun with --update-snapshots");
+        return false;
+    }
+    let expected = fs::read_to_string(&path).expect("Failed to read snapshot");
+    if expected == rendered {
+        info!("Snapshot {name} matches");
+        return true;
+    }
+    error!("Snapshot {name} does not match {}\n{}", path.display(), diff(&expected, rendered));
+    false
+}
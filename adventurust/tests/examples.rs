@@ -0,0 +1,131 @@
+use std::process::Command;
+
+/*
+Runs a binary on one of the examples from the puzzle statement and checks the answers
+it prints against the ones given in the statement. Inputs made up to catch a particular
+case live in `tests/inputs/`.
+*/
+
+fn input_path(day: &str, file_name: &str) -> String {
+    format!("{}/../prompts/{day}/{file_name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn test_input_path(file_name: &str) -> String {
+    format!("{}/tests/inputs/{file_name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn assert_answers(binary: &str, input: String, args: &[&str], expected: &[&str]) {
+    let output = Command::new(binary)
+        .arg(input.as_str())
+        .args(args)
+        .env("RUST_LOG", "error")
+        .output()
+        .expect("Failed to run binary");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for answer in expected {
+        assert!(stdout.lines().any(|line| line == *answer), "Expected {answer:?} from {args:?} in:\n{stdout}");
+    }
+}
+
+#[test]
+fn december_2_example() {
+    assert_answers(
+        env!("CARGO_BIN_EXE_december_2"),
+        input_path("december_2", "small_input.txt"),
+        &[],
+        &["Answer 1: 2", "Answer 2: 4"],
+    );
+}
+
+/*
+`1 5` is fixed by removing either level, the old window around the first failure removed
+one and then indexed an empty slice. `1 2 9 9 3 4` needs both nines gone and `1 9 9 9 2 3`
+needs three removals, so it's never safe here.
+*/
+#[test]
+fn december_2_dampener() {
+    let binary = env!("CARGO_BIN_EXE_december_2");
+    assert_answers(binary, test_input_path("december_2_dampener.txt"), &[], &["Answer 1: 0", "Answer 2: 1"]);
+    assert_answers(binary, test_input_path("december_2_dampener.txt"), &["--removals", "2"], &["Answer 2: 2"]);
+}
+
+// Reading a few bytes at a time splits instructions across reads, the answers and offsets shouldn't change
+#[test]
+fn december_3_example_in_small_chunks() {
+    for chunk_size in ["65536", "1", "2", "3"] {
+        assert_answers(
+            env!("CARGO_BIN_EXE_december_3"),
+            input_path("december_3", "small_input.txt"),
+            &["--trace", "--chunk-size", chunk_size],
+            &[
+                "        20  don't()              disabled",
+                "        64  mul(8,5)             +40, accumulator 48",
+                "First Answer: 161",
+                "Second Answer: 48",
+            ],
+        );
+    }
+}
+
+#[test]
+fn december_5_example() {
+    assert_answers(
+        env!("CARGO_BIN_EXE_december_5"),
+        input_path("december_5", "small_input.txt"),
+        &[],
+        &["Answer 1: 143", "Answer 2: 123"],
+    );
+}
+
+// 1|2 2|3 3|1 can't be satisfied, so 3,2,1 is reported rather than looped on or mis-sorted
+#[test]
+fn december_5_rule_cycle() {
+    assert_answers(
+        env!("CARGO_BIN_EXE_december_5"),
+        test_input_path("december_5_cycle.txt"),
+        &["--explain"],
+        &["update 3,2,1", "  can't be fixed, the rules loop through pages [1, 2, 3]"],
+    );
+}
+
+#[test]
+fn december_6_example() {
+    assert_answers(
+        env!("CARGO_BIN_EXE_december_6"),
+        input_path("december_6", "small_input.txt"),
+        &[],
+        &["Answer 1: 41", "Answer 2: 6"],
+    );
+}
+
+// The six positions drawn in the puzzle, along with the count so there can't be extras
+#[test]
+fn december_6_obstructions() {
+    assert_answers(
+        env!("CARGO_BIN_EXE_december_6"),
+        input_path("december_6", "small_input.txt"),
+        &["--list-obstructions"],
+        &[
+            "Answer 2: 6",
+            "Obstruction at row 6 column 3",
+            "Obstruction at row 7 column 6",
+            "Obstruction at row 7 column 7",
+            "Obstruction at row 8 column 1",
+            "Obstruction at row 8 column 3",
+            "Obstruction at row 9 column 7",
+        ],
+    );
+}
+
+#[test]
+fn december_7_example() {
+    for solver in ["backward", "search", "recursive"] {
+        assert_answers(
+            env!("CARGO_BIN_EXE_december_7"),
+            input_path("december_7", "small_input.txt"),
+            &["--solver", solver],
+            &["Answer 1: 3749", "Answer 2: 11387"],
+        );
+    }
+}
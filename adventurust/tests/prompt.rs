@@ -0,0 +1,44 @@
+use adventurust::prompt::{extract_examples, prompts_dir, read_prompt, Example};
+
+/*
+Checks the example blocks pulled out of the real prompts, since the prose check is a
+heuristic and each day's statement is laid out a little differently.
+*/
+
+fn examples(day: u32) -> Vec<Example> {
+    extract_examples(&read_prompt(&prompts_dir(), day))
+}
+
+#[test]
+fn december_4_grids() {
+    let examples = examples(4);
+    let sizes: Vec<usize> = examples.iter().map(|example| example.lines.len()).collect();
+    assert_eq!(sizes, vec![5, 10, 10, 3, 10]);
+    assert_eq!(examples[1].lines[0], "MMMSXXMASM");
+    assert_eq!(examples[3].text(), "M.S\n.A.\nM.S\n");
+}
+
+#[test]
+fn december_5_rules_and_updates_stay_together() {
+    let examples = examples(5);
+    let example = &examples[0];
+    assert_eq!(example.lines.len(), 28);
+    assert_eq!(example.lines[0], "47|53");
+    assert_eq!(example.lines[21], "");
+    assert_eq!(example.lines[22], "75,47,61,53,29");
+    assert_eq!(example.lines.last().unwrap(), "97,13,75,29,47");
+    // The correctly ordered updates quoted after the example are their own block
+    assert_eq!(examples[1].lines, vec!["75,47,61,53,29", "97,61,53,29,13", "75,29,13"]);
+}
+
+#[test]
+fn december_13_machines_stay_together() {
+    let examples = examples(13);
+    let machines: Vec<&Example> = examples.iter().filter(|example| example.lines[0].starts_with("Button A")).collect();
+    assert_eq!(machines.len(), 2);
+    for example in machines {
+        assert_eq!(example.lines.len(), 15);
+        assert_eq!(example.lines.iter().filter(|line| line.starts_with("Prize")).count(), 4);
+        assert_eq!(example.lines.iter().filter(|line| line.is_empty()).count(), 3);
+    }
+}
@@ -0,0 +1,39 @@
+use std::process::Command;
+
+/*
+Runs each binary against its small input and checks the rendered output against
+`tests/snapshots/`. To accept a new rendering run the binary by hand with
+`--snapshot <name> --update-snapshots`.
+*/
+
+fn input_path(day: &str, file_name: &str) -> String {
+    format!("{}/../prompts/{day}/{file_name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn assert_snapshot(binary: &str, input: String, name: &str) {
+    let output = Command::new(binary)
+        .args([input.as_str(), "--snapshot", name])
+        .env("RUST_LOG", "error")
+        .output()
+        .expect("Failed to run binary");
+    assert!(
+        output.status.success(),
+        "Snapshot {name} failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn december_6_map() {
+    assert_snapshot(env!("CARGO_BIN_EXE_december_6"), input_path("december_6", "small_input.txt"), "december_6_small");
+}
+
+#[test]
+fn december_8_antinodes() {
+    assert_snapshot(env!("CARGO_BIN_EXE_december_8"), input_path("december_8", "small_input.txt"), "december_8_small");
+}
+
+#[test]
+fn december_9_file_layout() {
+    assert_snapshot(env!("CARGO_BIN_EXE_december_9"), input_path("december_9", "small_input.txt"), "december_9_small");
+}